@@ -0,0 +1,20 @@
+<?xml version="1.0" encoding="UTF-8"?>
+<tileset version="1.10" tiledversion="1.10.2" name="spritesheet" tilewidth="8" tileheight="8" spacing="1" tilecount="24" columns="8">
+ <image source="../spritesheet.png" width="71" height="26"/>
+ <tile id="1">
+  <properties>
+   <property name="encounter_region" value="Forest"/>
+  </properties>
+ </tile>
+ <tile id="5">
+  <properties>
+   <property name="collider" type="bool" value="true"/>
+  </properties>
+ </tile>
+ <tile id="7">
+  <properties>
+   <property name="encounter_region" value="Fields"/>
+   <property name="tags" value="grass"/>
+  </properties>
+ </tile>
+</tileset>
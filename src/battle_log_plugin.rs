@@ -0,0 +1,175 @@
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+
+use crate::{AppState, UiFont};
+
+/// Lines shown at the same time on the window
+const VISIBLE_LINES: usize = 4;
+/// Lines kept on the history, the oldest ones are forgotten
+const MAX_HISTORY: usize = 64;
+/// Characters revealed per second by the typewriter
+const CHARS_PER_SECOND: f32 = 80.0;
+const TEXT_COLOR: Color = Color::rgb(1.0, 1.0, 1.0);
+/// Color of the lines scrolled up from the bottom
+const HISTORY_COLOR: Color = Color::rgb(0.6, 0.6, 0.6);
+
+// Plugin struct definitions
+/// Line for the battle log, sent by the `CombatPlugin`
+pub struct BattleLogEvent(pub String);
+
+#[derive(Debug, Component)]
+struct BattleLogRoot;
+
+#[derive(Debug, Component)]
+struct BattleLogText;
+
+#[derive(Debug, Default)]
+struct BattleLog {
+    /// Lines of the combat, the last one could still be typing
+    lines: Vec<String>,
+    /// Lines waiting for the typewriter
+    queue: VecDeque<String>,
+    /// Characters of the last line already shown
+    typed: f32,
+    /// Lines scrolled up from the bottom of the history
+    scroll: usize,
+}
+
+impl BattleLog {
+    fn typing(&self) -> bool {
+        self.lines
+            .last()
+            .is_some_and(|line| (self.typed as usize) < line.chars().count())
+    }
+}
+
+pub struct BattleLogPlugin;
+impl Plugin for BattleLogPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<BattleLogEvent>()
+            .init_resource::<BattleLog>();
+
+        app.add_system_set(SystemSet::on_enter(AppState::Combat).with_system(spawn_battle_log))
+            .add_system_set(
+                SystemSet::on_update(AppState::Combat)
+                    .with_system(receive_log_lines)
+                    .with_system(type_log_lines.after(receive_log_lines))
+                    .with_system(scroll_log)
+                    .with_system(update_log_text.after(type_log_lines).after(scroll_log)),
+            )
+            .add_system_set(SystemSet::on_exit(AppState::Combat).with_system(despawn_battle_log));
+    }
+}
+
+fn spawn_battle_log(mut commands: Commands, mut log: ResMut<BattleLog>) {
+    *log = BattleLog::default();
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: Rect {
+                    left: Val::Px(16.0),
+                    right: Val::Px(16.0),
+                    top: Val::Px(16.0),
+                    ..Default::default()
+                },
+                padding: Rect::all(Val::Px(8.0)),
+                min_size: Size::new(Val::Auto, Val::Px(88.0)),
+                ..Default::default()
+            },
+            color: UiColor(Color::rgba(0.0, 0.0, 0.3, 0.8)),
+            ..Default::default()
+        })
+        .insert(Name::new("Battle log"))
+        .insert(BattleLogRoot)
+        .with_children(|parent| {
+            parent
+                .spawn_bundle(TextBundle::default())
+                .insert(BattleLogText);
+        });
+}
+
+fn despawn_battle_log(mut commands: Commands, root_query: Query<Entity, With<BattleLogRoot>>) {
+    for ent in root_query.iter() {
+        commands.entity(ent).despawn_recursive();
+    }
+}
+
+fn receive_log_lines(mut log_event: EventReader<BattleLogEvent>, mut log: ResMut<BattleLog>) {
+    for event in log_event.iter() {
+        log.queue.push_back(event.0.clone());
+    }
+}
+
+/// Type the last line and start the next one when it is done.
+/// If too many lines are waiting the current one is shown at once to catch up
+fn type_log_lines(time: Res<Time>, mut log: ResMut<BattleLog>) {
+    if log.typing() {
+        if log.queue.len() >= VISIBLE_LINES {
+            log.typed = f32::MAX;
+        } else {
+            log.typed += CHARS_PER_SECOND * time.delta_seconds();
+        }
+        return;
+    }
+    if let Some(line) = log.queue.pop_front() {
+        log.lines.push(line);
+        if log.lines.len() > MAX_HISTORY {
+            log.lines.remove(0);
+        }
+        log.typed = 0.0;
+        log.scroll = 0;
+    }
+}
+
+fn scroll_log(mut keyboard: ResMut<Input<KeyCode>>, mut log: ResMut<BattleLog>) {
+    let max_scroll = log.lines.len().saturating_sub(VISIBLE_LINES);
+    if keyboard.just_pressed(KeyCode::PageUp) {
+        log.scroll = usize::min(log.scroll + 1, max_scroll);
+        keyboard.reset(KeyCode::PageUp);
+    }
+    if keyboard.just_pressed(KeyCode::PageDown) {
+        log.scroll = log.scroll.saturating_sub(1);
+        keyboard.reset(KeyCode::PageDown);
+    }
+}
+
+fn update_log_text(
+    log: Res<BattleLog>,
+    font: Res<UiFont>,
+    mut text_query: Query<&mut Text, With<BattleLogText>>,
+) {
+    if !log.is_changed() {
+        return;
+    }
+    let end = log.lines.len() - log.scroll;
+    let start = end.saturating_sub(VISIBLE_LINES);
+    let color = if log.scroll > 0 {
+        HISTORY_COLOR
+    } else {
+        TEXT_COLOR
+    };
+    let sections: Vec<_> = log.lines[start..end]
+        .iter()
+        .enumerate()
+        .map(|(i, line)| {
+            let value = if log.scroll == 0 && start + i == end - 1 {
+                line.chars().take(log.typed as usize).collect()
+            } else {
+                line.clone()
+            };
+            TextSection {
+                value: format!("{value}\n"),
+                style: TextStyle {
+                    font: font.0.clone(),
+                    font_size: 16.0,
+                    color,
+                },
+            }
+        })
+        .collect();
+    for mut text in text_query.iter_mut() {
+        text.sections = sections.clone();
+    }
+}
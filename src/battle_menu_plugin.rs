@@ -0,0 +1,485 @@
+use bevy::prelude::*;
+
+use crate::{
+    combat_plugin::{ActingSide, ActiveMember, CombatAction, CombatEvent, CombatState},
+    common_component::{AtbGauge, CombatStats},
+    enemy_plugin::Enemy,
+    inventory_plugin::{Inventory, ItemData, ItemDatabase, ItemDatabaseHandle},
+    skill_plugin::{
+        SkillData, SkillDatabase, SkillDatabaseHandle, SkillEffect, SkillTarget, Skills,
+    },
+    UiFont,
+};
+
+const COMMANDS: [&str; 4] = ["Attack", "Magic", "Item", "Flee"];
+const SELECTED_COLOR: Color = Color::rgb(1.0, 1.0, 1.0);
+const UNSELECTED_COLOR: Color = Color::rgb(0.5, 0.5, 0.5);
+/// Options that can not be chosen, like skills without enough mp
+const DISABLED_COLOR: Color = Color::rgb(0.25, 0.25, 0.25);
+
+type CombatantQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        Entity,
+        &'static Name,
+        &'static CombatStats,
+        &'static Transform,
+        Option<&'static Enemy>,
+    ),
+>;
+
+// Plugin struct definitions
+#[derive(Debug, Component)]
+struct BattleMenuRoot;
+
+#[derive(Debug, Component)]
+struct BattleMenuText;
+
+/// Marks the combatant that is going to be targeted
+#[derive(Debug, Component)]
+struct TargetCursor;
+
+/// Side of the combat, relative to the party
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+enum TargetSide {
+    Enemies,
+    Allies,
+    /// Dead allies, only for revives
+    FallenAllies,
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+enum MenuPage {
+    Commands,
+    Magic,
+    Item,
+    /// Choosing the target of the selected action
+    Target(TargetSide),
+}
+
+/// Option shown on the menu
+struct MenuEntry {
+    label: String,
+    enabled: bool,
+}
+
+impl MenuEntry {
+    fn new(label: impl Into<String>) -> Self {
+        Self {
+            label: label.into(),
+            enabled: true,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct BattleMenu {
+    page: MenuPage,
+    cursor: usize,
+    /// Action waiting for a target
+    action: Option<CombatAction>,
+}
+
+impl Default for BattleMenu {
+    fn default() -> Self {
+        Self {
+            page: MenuPage::Commands,
+            cursor: 0,
+            action: None,
+        }
+    }
+}
+
+impl BattleMenu {
+    fn open(&mut self, page: MenuPage) {
+        self.page = page;
+        self.cursor = 0;
+    }
+}
+
+pub struct BattleMenuPlugin;
+impl Plugin for BattleMenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<BattleMenu>();
+
+        app.add_system_set(
+            SystemSet::on_enter(CombatState::PlayerTurn).with_system(spawn_battle_menu),
+        )
+        .add_system_set(
+            SystemSet::on_update(CombatState::PlayerTurn)
+                .with_system(menu_input)
+                .with_system(update_menu_text.after(menu_input))
+                .with_system(move_target_cursor.after(menu_input)),
+        )
+        .add_system_set(
+            SystemSet::on_exit(CombatState::PlayerTurn).with_system(despawn_battle_menu),
+        );
+    }
+}
+
+fn spawn_battle_menu(mut commands: Commands, mut menu: ResMut<BattleMenu>) {
+    *menu = BattleMenu::default();
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: Rect {
+                    left: Val::Px(16.0),
+                    bottom: Val::Px(16.0),
+                    ..Default::default()
+                },
+                padding: Rect::all(Val::Px(8.0)),
+                min_size: Size::new(Val::Px(160.0), Val::Auto),
+                ..Default::default()
+            },
+            color: UiColor(Color::rgba(0.0, 0.0, 0.3, 0.8)),
+            ..Default::default()
+        })
+        .insert(Name::new("Battle menu"))
+        .insert(BattleMenuRoot)
+        .with_children(|parent| {
+            parent
+                .spawn_bundle(TextBundle {
+                    text: Text {
+                        sections: Vec::new(),
+                        alignment: TextAlignment::default(),
+                    },
+                    ..Default::default()
+                })
+                .insert(BattleMenuText);
+        });
+
+    commands
+        .spawn_bundle(SpriteBundle {
+            sprite: Sprite {
+                color: SELECTED_COLOR,
+                custom_size: Some(Vec2::new(4.0, 1.0)),
+                ..Default::default()
+            },
+            visibility: Visibility { is_visible: false },
+            ..Default::default()
+        })
+        .insert(Name::new("Target cursor"))
+        .insert(TargetCursor);
+}
+
+fn despawn_battle_menu(
+    mut commands: Commands,
+    menu_query: Query<Entity, With<BattleMenuRoot>>,
+    cursor_query: Query<Entity, With<TargetCursor>>,
+) {
+    for ent in menu_query.iter().chain(cursor_query.iter()) {
+        commands.entity(ent).despawn_recursive();
+    }
+}
+
+/// Combatants of one side sorted from left to right and from top to bottom
+fn side_targets(side: TargetSide, combatant_query: &CombatantQuery) -> Vec<(Entity, String, Vec3)> {
+    let mut targets: Vec<_> = combatant_query
+        .iter()
+        .filter(|(_, _, combat_stats, _, enemy)| match side {
+            TargetSide::Enemies => enemy.is_some() && combat_stats.hp > 0,
+            TargetSide::Allies => enemy.is_none() && combat_stats.hp > 0,
+            TargetSide::FallenAllies => enemy.is_none() && combat_stats.hp <= 0,
+        })
+        .map(|(entity, name, _, transform, _)| (entity, name.to_string(), transform.translation))
+        .collect();
+    targets.sort_by(|a, b| a.2.x.total_cmp(&b.2.x).then(b.2.y.total_cmp(&a.2.y)));
+    targets
+}
+
+/// Skills of the member that exist on the database
+fn known_skills<'a>(skills: &Skills, database: &'a SkillDatabase) -> Vec<&'a SkillData> {
+    skills
+        .0
+        .iter()
+        .filter_map(|name| database.get(name))
+        .collect()
+}
+
+/// Items of the inventory that exist on the database, with how many are left
+fn known_items<'a>(inventory: &Inventory, database: &'a ItemDatabase) -> Vec<(&'a ItemData, u32)> {
+    inventory
+        .items
+        .iter()
+        .filter_map(|stack| database.get(&stack.item).map(|item| (item, stack.count)))
+        .collect()
+}
+
+/// Options shown on the current page of the menu
+fn menu_entries(
+    menu: &BattleMenu,
+    member_stats: &CombatStats,
+    skills: &[&SkillData],
+    items: &[(&ItemData, u32)],
+    targets: &[(Entity, String, Vec3)],
+) -> Vec<MenuEntry> {
+    match menu.page {
+        MenuPage::Commands => COMMANDS.iter().map(|c| MenuEntry::new(*c)).collect(),
+        MenuPage::Magic => skills
+            .iter()
+            .map(|skill| MenuEntry {
+                label: format!("{:<8}{:>3}", skill.name, skill.mp_cost),
+                enabled: member_stats.mp >= skill.mp_cost,
+            })
+            .collect(),
+        MenuPage::Item => items
+            .iter()
+            .map(|(item, count)| MenuEntry::new(format!("{:<12}{:>3}", item.name, count)))
+            .collect(),
+        MenuPage::Target(_) => targets
+            .iter()
+            .map(|(_, name, _)| MenuEntry::new(name.clone()))
+            .collect(),
+    }
+}
+
+/// Targets listed on the current page of the menu
+fn page_targets(
+    menu: &BattleMenu,
+    combatant_query: &CombatantQuery,
+) -> Vec<(Entity, String, Vec3)> {
+    match menu.page {
+        MenuPage::Target(side) => side_targets(side, combatant_query),
+        _ => Vec::new(),
+    }
+}
+
+/// Targets of an action that don't need to be chosen,
+/// otherwise the target page is opened and the action waits there
+fn action_targets(
+    menu: &mut BattleMenu,
+    action: CombatAction,
+    target: SkillTarget,
+    effect: &SkillEffect,
+    emitter: Entity,
+    combatant_query: &CombatantQuery,
+) -> Option<(Vec<Entity>, CombatAction)> {
+    let side = match target {
+        SkillTarget::User => return Some((vec![emitter], action)),
+        SkillTarget::AllEnemies => {
+            let targets = side_targets(TargetSide::Enemies, combatant_query)
+                .into_iter()
+                .map(|(target, _, _)| target)
+                .collect();
+            return Some((targets, action));
+        }
+        SkillTarget::Enemy => TargetSide::Enemies,
+        SkillTarget::Ally if matches!(effect, SkillEffect::Revive(_)) => TargetSide::FallenAllies,
+        SkillTarget::Ally => TargetSide::Allies,
+    };
+    menu.action = Some(action);
+    menu.open(MenuPage::Target(side));
+    None
+}
+
+#[allow(clippy::too_many_arguments)]
+fn menu_input(
+    mut keyboard: ResMut<Input<KeyCode>>,
+    mut menu: ResMut<BattleMenu>,
+    mut combat_event: EventWriter<CombatEvent>,
+    mut combat_state: ResMut<State<CombatState>>,
+    mut acting_side: ResMut<ActingSide>,
+    active_member: Res<ActiveMember>,
+    inventory: Res<Inventory>,
+    skill_handle: Res<SkillDatabaseHandle>,
+    skill_databases: Res<Assets<SkillDatabase>>,
+    item_handle: Res<ItemDatabaseHandle>,
+    item_databases: Res<Assets<ItemDatabase>>,
+    combatant_query: CombatantQuery,
+    mut member_query: Query<(&CombatStats, &Skills, &mut AtbGauge)>,
+) {
+    let skill_database = skill_databases
+        .get(&skill_handle.0)
+        .expect("Skill database not loaded 'BattleMenuPlugin (menu_input)'");
+    let item_database = item_databases
+        .get(&item_handle.0)
+        .expect("Item database not loaded 'BattleMenuPlugin (menu_input)'");
+    let emitter = match active_member.0 {
+        Some(emitter) => emitter,
+        None => return,
+    };
+    let (member_stats, skills, mut gauge) = member_query
+        .get_mut(emitter)
+        .expect("Can not get the active party member");
+    let skills = known_skills(skills, skill_database);
+    let items = known_items(&inventory, item_database);
+    let targets = page_targets(&menu, &combatant_query);
+    let entries = menu_entries(&menu, member_stats, &skills, &items, &targets);
+
+    if !entries.is_empty() && keyboard.just_pressed(KeyCode::Up) {
+        menu.cursor = (menu.cursor + entries.len() - 1) % entries.len();
+    }
+    if !entries.is_empty() && keyboard.just_pressed(KeyCode::Down) {
+        menu.cursor = (menu.cursor + 1) % entries.len();
+    }
+    if keyboard.just_pressed(KeyCode::Back) {
+        keyboard.reset(KeyCode::Back);
+        menu.action = None;
+        menu.open(MenuPage::Commands);
+    }
+    if !keyboard.just_pressed(KeyCode::Space) && !keyboard.just_pressed(KeyCode::Return) {
+        return;
+    }
+    // The turn can end on this same frame, don't let the key press leak into the next turn
+    keyboard.reset(KeyCode::Space);
+    keyboard.reset(KeyCode::Return);
+
+    if !entries.get(menu.cursor).is_some_and(|entry| entry.enabled) {
+        return;
+    }
+    let (targets, action) = match menu.page {
+        MenuPage::Commands => match menu.cursor {
+            0 => {
+                menu.action = Some(CombatAction::Attack);
+                menu.open(MenuPage::Target(TargetSide::Enemies));
+                return;
+            }
+            1 => {
+                menu.open(MenuPage::Magic);
+                return;
+            }
+            2 => {
+                menu.open(MenuPage::Item);
+                return;
+            }
+            _ => (Vec::new(), CombatAction::Flee),
+        },
+        MenuPage::Magic => {
+            let skill = skills[menu.cursor];
+            let action = CombatAction::Skill(skill.name.clone());
+            let targets = action_targets(
+                &mut menu,
+                action,
+                skill.target,
+                &skill.effect,
+                emitter,
+                &combatant_query,
+            );
+            match targets {
+                Some(targets) => targets,
+                None => return,
+            }
+        }
+        MenuPage::Item => {
+            let (item, _) = items[menu.cursor];
+            let action = CombatAction::Item(item.name.clone());
+            let targets = action_targets(
+                &mut menu,
+                action,
+                item.target,
+                &item.effect,
+                emitter,
+                &combatant_query,
+            );
+            match targets {
+                Some(targets) => targets,
+                None => return,
+            }
+        }
+        MenuPage::Target(_) => match (targets.get(menu.cursor), menu.action.take()) {
+            (Some((target, _, _)), Some(action)) => (vec![*target], action),
+            _ => return,
+        },
+    };
+
+    gauge.0 = 0.0;
+    combat_event.send(CombatEvent {
+        targets,
+        emitter,
+        action,
+    });
+    *acting_side = ActingSide::Player;
+    combat_state
+        .set(CombatState::ResolveActions)
+        .expect("Error setting CombatState::ResolveActions");
+}
+
+/// The name of the active member is shown on top of the options
+#[allow(clippy::too_many_arguments)]
+fn update_menu_text(
+    menu: Res<BattleMenu>,
+    font: Res<UiFont>,
+    active_member: Res<ActiveMember>,
+    inventory: Res<Inventory>,
+    skill_handle: Res<SkillDatabaseHandle>,
+    skill_databases: Res<Assets<SkillDatabase>>,
+    item_handle: Res<ItemDatabaseHandle>,
+    item_databases: Res<Assets<ItemDatabase>>,
+    combatant_query: CombatantQuery,
+    member_query: Query<(&Name, &CombatStats, &Skills)>,
+    mut text_query: Query<&mut Text, With<BattleMenuText>>,
+) {
+    let skill_database = skill_databases
+        .get(&skill_handle.0)
+        .expect("Skill database not loaded 'BattleMenuPlugin (update_menu_text)'");
+    let item_database = item_databases
+        .get(&item_handle.0)
+        .expect("Item database not loaded 'BattleMenuPlugin (update_menu_text)'");
+    let (member_name, member_stats, skills) = match active_member
+        .0
+        .and_then(|member| member_query.get(member).ok())
+    {
+        Some(member) => member,
+        None => return,
+    };
+    let skills = known_skills(skills, skill_database);
+    let items = known_items(&inventory, item_database);
+    let targets = page_targets(&menu, &combatant_query);
+    let mut entries = menu_entries(&menu, member_stats, &skills, &items, &targets);
+    if entries.is_empty() {
+        entries.push(MenuEntry {
+            label: String::from("Empty"),
+            enabled: false,
+        });
+    }
+    let header = TextSection {
+        value: format!("{member_name}\n"),
+        style: TextStyle {
+            font: font.0.clone(),
+            font_size: 20.0,
+            color: SELECTED_COLOR,
+        },
+    };
+    for mut text in text_query.iter_mut() {
+        text.sections = std::iter::once(header.clone())
+            .chain(entries.iter().enumerate().map(|(i, entry)| {
+                let selected = i == menu.cursor;
+                TextSection {
+                    value: format!("{}{}\n", if selected { "> " } else { "  " }, entry.label),
+                    style: TextStyle {
+                        font: font.0.clone(),
+                        font_size: 20.0,
+                        color: match (entry.enabled, selected) {
+                            (false, _) => DISABLED_COLOR,
+                            (true, true) => SELECTED_COLOR,
+                            (true, false) => UNSELECTED_COLOR,
+                        },
+                    },
+                }
+            }))
+            .collect();
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn move_target_cursor(
+    menu: Res<BattleMenu>,
+    combatant_query: CombatantQuery,
+    mut cursor_query: Query<
+        (&mut Transform, &mut Visibility),
+        (With<TargetCursor>, Without<CombatStats>),
+    >,
+) {
+    let targets = page_targets(&menu, &combatant_query);
+    for (mut transform, mut visibility) in cursor_query.iter_mut() {
+        match targets.get(menu.cursor) {
+            Some((_, _, translation)) => {
+                visibility.is_visible = true;
+                transform.translation = *translation + Vec3::new(0.0, 6.0, 1.0);
+            }
+            _ => visibility.is_visible = false,
+        }
+    }
+}
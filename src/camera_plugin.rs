@@ -29,6 +29,7 @@ fn setup_camera(mut commands: Commands, win_res: Res<Windows>) {
         bevy::render::camera::ScalingMode::FixedVertical;
     new_camera.orthographic_projection.scale = win.height() / (WIN_SCALE * 2.0);
     commands.spawn_bundle(new_camera);
+    commands.spawn_bundle(UiCameraBundle::default());
 }
 
 fn setup_letterboxing(mut commands: Commands) {
@@ -0,0 +1,308 @@
+use bevy::prelude::*;
+
+use crate::{
+    common_component::CombatStats, enemy_plugin::Enemy, party_plugin::Party, AppState, UiFont,
+};
+
+const TEXT_COLOR: Color = Color::rgb(1.0, 1.0, 1.0);
+const BAR_BACKGROUND_COLOR: Color = Color::rgb(0.15, 0.15, 0.15);
+const HP_COLOR: Color = Color::rgb(0.2, 0.8, 0.2);
+const MP_COLOR: Color = Color::rgb(0.3, 0.5, 1.0);
+/// Size of the bars on the party panel, in pixels
+const PANEL_BAR_WIDTH: f32 = 64.0;
+const PANEL_BAR_HEIGHT: f32 = 8.0;
+/// Size of the bars under the enemies, in world units
+const ENEMY_BAR_WIDTH: f32 = 8.0;
+const ENEMY_BAR_HEIGHT: f32 = 1.0;
+/// Distance between the center of the enemy and its bar
+const ENEMY_BAR_OFFSET: f32 = 6.0;
+
+// Plugin struct definitions
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+enum Stat {
+    Hp,
+    Mp,
+}
+
+impl Stat {
+    fn values(&self, combat_stats: &CombatStats) -> (i32, i32) {
+        match self {
+            Stat::Hp => (combat_stats.hp, combat_stats.max_hp),
+            Stat::Mp => (combat_stats.mp, combat_stats.max_mp),
+        }
+    }
+
+    /// Filled part of the bar, from 0.0 to 1.0
+    fn ratio(&self, combat_stats: &CombatStats) -> f32 {
+        match self.values(combat_stats) {
+            (_, max) if max <= 0 => 0.0,
+            (value, max) => (value as f32 / max as f32).clamp(0.0, 1.0),
+        }
+    }
+
+    fn label(&self, combat_stats: &CombatStats) -> String {
+        let (value, max) = self.values(combat_stats);
+        let name = match self {
+            Stat::Hp => "HP",
+            Stat::Mp => "MP",
+        };
+        format!("{name} {:>3}/{:>3}", value.max(0), max)
+    }
+}
+
+#[derive(Debug, Component)]
+struct PartyPanelRoot;
+
+/// Text with a stat of a party member
+#[derive(Debug, Component)]
+struct StatText {
+    owner: Entity,
+    stat: Stat,
+}
+
+/// Filled part of a bar on the party panel
+#[derive(Debug, Component)]
+struct StatBar {
+    owner: Entity,
+    stat: Stat,
+}
+
+/// Background and fill of the bar under an enemy, children of the enemy
+#[derive(Debug, Component)]
+struct EnemyHpBar;
+
+#[derive(Debug, Component)]
+struct EnemyHpBarFill;
+
+pub struct CombatHudPlugin;
+impl Plugin for CombatHudPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(SystemSet::on_enter(AppState::Combat).with_system(spawn_party_panel))
+            .add_system_set(
+                SystemSet::on_update(AppState::Combat)
+                    .with_system(update_party_panel)
+                    .with_system(add_enemy_hp_bars)
+                    .with_system(update_enemy_hp_bars),
+            )
+            .add_system_set(SystemSet::on_exit(AppState::Combat).with_system(despawn_party_panel));
+    }
+}
+
+fn text_style(font: &Handle<Font>) -> TextStyle {
+    TextStyle {
+        font: font.clone(),
+        font_size: 16.0,
+        color: TEXT_COLOR,
+    }
+}
+
+fn spawn_stat(
+    parent: &mut ChildBuilder,
+    font: &Handle<Font>,
+    owner: Entity,
+    stat: Stat,
+    combat_stats: &CombatStats,
+) {
+    parent
+        .spawn_bundle(TextBundle {
+            text: Text::with_section(
+                stat.label(combat_stats),
+                text_style(font),
+                TextAlignment::default(),
+            ),
+            ..Default::default()
+        })
+        .insert(StatText { owner, stat });
+    parent
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Px(PANEL_BAR_WIDTH), Val::Px(PANEL_BAR_HEIGHT)),
+                margin: Rect {
+                    left: Val::Px(4.0),
+                    right: Val::Px(8.0),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            color: UiColor(BAR_BACKGROUND_COLOR),
+            ..Default::default()
+        })
+        .with_children(|parent| {
+            parent
+                .spawn_bundle(NodeBundle {
+                    style: Style {
+                        size: Size::new(
+                            Val::Percent(stat.ratio(combat_stats) * 100.0),
+                            Val::Percent(100.0),
+                        ),
+                        ..Default::default()
+                    },
+                    color: UiColor(match stat {
+                        Stat::Hp => HP_COLOR,
+                        Stat::Mp => MP_COLOR,
+                    }),
+                    ..Default::default()
+                })
+                .insert(StatBar { owner, stat });
+        });
+}
+
+/// One row for each party member, in party order
+fn spawn_party_panel(
+    mut commands: Commands,
+    font: Res<UiFont>,
+    party: Res<Party>,
+    member_query: Query<(&Name, &CombatStats)>,
+) {
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: Rect {
+                    right: Val::Px(16.0),
+                    bottom: Val::Px(16.0),
+                    ..Default::default()
+                },
+                flex_direction: FlexDirection::ColumnReverse,
+                padding: Rect::all(Val::Px(8.0)),
+                ..Default::default()
+            },
+            color: UiColor(Color::rgba(0.0, 0.0, 0.3, 0.8)),
+            ..Default::default()
+        })
+        .insert(Name::new("Party panel"))
+        .insert(PartyPanelRoot)
+        .with_children(|parent| {
+            for member in party.members.iter() {
+                let (name, combat_stats) = match member_query.get(*member) {
+                    Ok(member) => member,
+                    Err(_) => continue,
+                };
+                parent
+                    .spawn_bundle(NodeBundle {
+                        style: Style {
+                            align_items: AlignItems::Center,
+                            ..Default::default()
+                        },
+                        color: UiColor(Color::NONE),
+                        ..Default::default()
+                    })
+                    .with_children(|parent| {
+                        parent.spawn_bundle(TextBundle {
+                            style: Style {
+                                min_size: Size::new(Val::Px(72.0), Val::Auto),
+                                ..Default::default()
+                            },
+                            text: Text::with_section(
+                                name.as_str(),
+                                text_style(&font.0),
+                                TextAlignment::default(),
+                            ),
+                            ..Default::default()
+                        });
+                        spawn_stat(parent, &font.0, *member, Stat::Hp, combat_stats);
+                        spawn_stat(parent, &font.0, *member, Stat::Mp, combat_stats);
+                    });
+            }
+        });
+}
+
+fn despawn_party_panel(mut commands: Commands, panel_query: Query<Entity, With<PartyPanelRoot>>) {
+    for ent in panel_query.iter() {
+        commands.entity(ent).despawn_recursive();
+    }
+}
+
+/// Only the members whose `CombatStats` changed are updated
+fn update_party_panel(
+    changed_query: Query<&CombatStats, Changed<CombatStats>>,
+    mut text_query: Query<(&StatText, &mut Text)>,
+    mut bar_query: Query<(&StatBar, &mut Style)>,
+) {
+    for (stat_text, mut text) in text_query.iter_mut() {
+        if let Ok(combat_stats) = changed_query.get(stat_text.owner) {
+            text.sections[0].value = stat_text.stat.label(combat_stats);
+        }
+    }
+    for (stat_bar, mut style) in bar_query.iter_mut() {
+        if let Ok(combat_stats) = changed_query.get(stat_bar.owner) {
+            style.size.width = Val::Percent(stat_bar.stat.ratio(combat_stats) * 100.0);
+        }
+    }
+}
+
+/// Size and position of the fill, it stays aligned to the left of the bar
+fn enemy_bar_fill(ratio: f32) -> (Vec2, Vec3) {
+    let width = ENEMY_BAR_WIDTH * ratio;
+    (
+        Vec2::new(width, ENEMY_BAR_HEIGHT),
+        Vec3::new((width - ENEMY_BAR_WIDTH) / 2.0, -ENEMY_BAR_OFFSET, 0.2),
+    )
+}
+
+fn add_enemy_hp_bars(
+    mut commands: Commands,
+    enemy_query: Query<(Entity, &CombatStats), Added<Enemy>>,
+) {
+    for (enemy, combat_stats) in enemy_query.iter() {
+        let (size, translation) = enemy_bar_fill(Stat::Hp.ratio(combat_stats));
+        commands.entity(enemy).with_children(|parent| {
+            parent
+                .spawn_bundle(SpriteBundle {
+                    sprite: Sprite {
+                        color: BAR_BACKGROUND_COLOR,
+                        custom_size: Some(Vec2::new(ENEMY_BAR_WIDTH, ENEMY_BAR_HEIGHT)),
+                        ..Default::default()
+                    },
+                    transform: Transform::from_xyz(0.0, -ENEMY_BAR_OFFSET, 0.1),
+                    ..Default::default()
+                })
+                .insert(Name::new("HP bar"))
+                .insert(EnemyHpBar);
+            parent
+                .spawn_bundle(SpriteBundle {
+                    sprite: Sprite {
+                        color: HP_COLOR,
+                        custom_size: Some(size),
+                        ..Default::default()
+                    },
+                    transform: Transform::from_translation(translation),
+                    ..Default::default()
+                })
+                .insert(Name::new("HP bar fill"))
+                .insert(EnemyHpBar)
+                .insert(EnemyHpBarFill);
+        });
+    }
+}
+
+/// The children don't inherit the visibility, the bars are hidden with the dead enemy
+#[allow(clippy::type_complexity)]
+fn update_enemy_hp_bars(
+    enemy_query: Query<(&CombatStats, &Children), (With<Enemy>, Changed<CombatStats>)>,
+    mut bar_query: Query<
+        (
+            &mut Sprite,
+            &mut Transform,
+            &mut Visibility,
+            Option<&EnemyHpBarFill>,
+        ),
+        With<EnemyHpBar>,
+    >,
+) {
+    for (combat_stats, children) in enemy_query.iter() {
+        for child in children.iter() {
+            let (mut sprite, mut transform, mut visibility, fill) = match bar_query.get_mut(*child)
+            {
+                Ok(bar) => bar,
+                Err(_) => continue,
+            };
+            visibility.is_visible = combat_stats.hp > 0;
+            if fill.is_some() {
+                let (size, translation) = enemy_bar_fill(Stat::Hp.ratio(combat_stats));
+                sprite.custom_size = Some(size);
+                transform.translation = translation;
+            }
+        }
+    }
+}
@@ -2,123 +2,707 @@ use bevy::prelude::*;
 use bevy_inspector_egui::Inspectable;
 use rand::Rng;
 
-use crate::{common_component::CombatStats, player_plugin::Player, AppState, SpriteSheet};
+use crate::{
+    battle_log_plugin::BattleLogEvent,
+    common_component::{AtbGauge, CombatStats, EffectiveStats},
+    damage::{roll_damage, DamageConfig, DamageInput, DamageOutcome},
+    element::{Affinities, Affinity, Element},
+    enemy_ai::{AiAction, AiProfile},
+    enemy_plugin::{BlocksEscape, Enemy},
+    fadeout_plugin::FadeoutConfigResource,
+    hit_feedback_plugin::{HitEvent, HitOutcome},
+    inventory_plugin::{Inventory, ItemDatabase, ItemDatabaseHandle},
+    party_plugin::{Party, PartyMember},
+    skill_plugin::{SkillDatabase, SkillDatabaseHandle, SkillEffect, SkillTarget},
+    status_effect::{StatusEffects, StatusKind},
+    AppState, Autosave,
+};
 
-// TODO move structs to a create enemy plugin
-// Plugin struct definitions
-#[derive(Debug, Component, Inspectable)]
-pub struct Enemy;
+/// Chance to flee when the runner is as fast as the enemies
+const FLEE_BASE_CHANCE: f32 = 0.5;
+/// Flee chance gained for each point of speed over the fastest enemy
+const FLEE_CHANCE_PER_SPEED: f32 = 0.05;
 
-#[derive(Bundle)]
-struct EnemyBundle {
-    name: Name,
-    tag: Enemy,
-    combat_stats: CombatStats,
-    #[bundle]
-    sprite: SpriteSheetBundle,
+#[derive(Debug, Clone, PartialEq)]
+pub enum CombatAction {
+    Attack,
+    /// Use the skill with this name from the `SkillDatabase`
+    Skill(String),
+    /// Use one item with this name from the `Inventory`
+    Item(String),
+    /// Try to escape from the combat, it has no targets.
+    /// On failure the turn is lost
+    Flee,
 }
-// end TODO
 
 pub struct CombatEvent {
-    pub target: Entity,
+    pub targets: Vec<Entity>,
     pub emitter: Entity,
+    pub action: CombatAction,
 }
 
+/// Phases of a combat turn, only meaningful while on `AppState::Combat`
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum CombatState {
+    Inactive,
+    /// Active time battle only, the gauges fill until someone can act
+    Charging,
+    /// Waiting for the command of the `ActiveMember`
+    PlayerTurn,
+    /// Apply the `CombatEvent`s sent by the side that is acting
+    ResolveActions,
+    /// Every enemy that can act choose an action
+    EnemyTurn,
+    /// Look for victory or defeat before starting a new turn
+    TurnEnd,
+    /// Every enemy is dead, show the rewards until the player confirms
+    Victory,
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Inspectable)]
+pub enum TurnMode {
+    /// Every party member acts in order and then every enemy acts
+    TurnBased,
+    /// Each combatant acts when its `AtbGauge` is full
+    ActiveTime,
+}
+
+impl TurnMode {
+    /// Phase that starts every turn
+    fn turn_start(&self) -> CombatState {
+        match self {
+            TurnMode::TurnBased => CombatState::PlayerTurn,
+            TurnMode::ActiveTime => CombatState::Charging,
+        }
+    }
+}
+
+#[derive(Debug, Inspectable)]
+pub struct CombatConfig {
+    pub turn_mode: TurnMode,
+    /// Gauge filled per second for each point of speed
+    pub gauge_fill_rate: f32,
+}
+
+impl Default for CombatConfig {
+    fn default() -> Self {
+        Self {
+            turn_mode: TurnMode::TurnBased,
+            gauge_fill_rate: 4.0,
+        }
+    }
+}
+
+/// Side whose actions are being resolved
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum ActingSide {
+    Player,
+    Enemy,
+}
+
+/// Party member whose command is being chosen
+#[derive(Debug, Default)]
+pub struct ActiveMember(pub Option<Entity>);
+
+/// Party members that already had their turn, reset when a new turn starts
+#[derive(Debug, Default)]
+struct Acted(Vec<Entity>);
+
+/// Set when the player escapes from the combat
+#[derive(Debug, Default)]
+struct Escaped(bool);
+
 pub struct CombatPlugin;
 impl Plugin for CombatPlugin {
     fn build(&self, app: &mut App) {
-        app.add_event::<CombatEvent>();
+        app.add_event::<CombatEvent>()
+            .add_state(CombatState::Inactive)
+            .init_resource::<CombatConfig>()
+            .init_resource::<DamageConfig>()
+            .init_resource::<Escaped>()
+            .init_resource::<ActiveMember>()
+            .init_resource::<Acted>()
+            .insert_resource(ActingSide::Player);
+
+        app.add_system_set(SystemSet::on_enter(AppState::Combat).with_system(start_combat))
+            .add_system_set(SystemSet::on_exit(AppState::Combat).with_system(stop_combat));
 
-        app.add_system_set(SystemSet::on_enter(AppState::Combat).with_system(spawn_enemy))
+        app.add_system_set(SystemSet::on_update(CombatState::Charging).with_system(fill_gauges))
             .add_system_set(
-                SystemSet::on_update(AppState::Combat)
-                    .with_system(force_end_combat)
-                    .with_system(combat_input)
-                    .with_system(process_combat.after(combat_input))
-                    .with_system(end_combat.after(process_combat)),
+                SystemSet::on_enter(CombatState::PlayerTurn).with_system(start_player_turn),
             )
-            .add_system_set(SystemSet::on_exit(AppState::Combat).with_system(despawn_enemy));
-    }
-}
-
-fn spawn_enemy(mut commands: Commands, sprite_sheet: Res<SpriteSheet>) {
-    // TODO change to a file
-    let enemy_type = [("Rat", 16), ("Snake", 17), ("Wolf", 18)];
-
-    let selected_enemy = rand::thread_rng().gen_range(0..enemy_type.len());
-    commands.spawn_bundle(EnemyBundle {
-        tag: Enemy,
-        name: Name::new(enemy_type[selected_enemy].0),
-        // TODO diferent enemys diferent stats
-        combat_stats: CombatStats {
-            hp: 5,
-            max_hp: 5,
-            attack: 1,
-            defense: 1,
-        },
-        sprite: SpriteSheetBundle {
-            sprite: TextureAtlasSprite::new(enemy_type[selected_enemy].1),
-            texture_atlas: sprite_sheet.0.clone(),
-            transform: Transform::from_xyz(0.0, 0.0, 10.0),
-            ..Default::default()
-        },
-    });
+            .add_system_set(
+                SystemSet::on_update(CombatState::ResolveActions)
+                    .with_system(process_combat)
+                    .with_system(end_resolution.after(process_combat)),
+            )
+            .add_system_set(SystemSet::on_update(CombatState::EnemyTurn).with_system(enemy_turn))
+            .add_system_set(SystemSet::on_update(CombatState::TurnEnd).with_system(end_combat));
+    }
+}
+
+fn start_combat(
+    config: Res<CombatConfig>,
+    mut autosave: ResMut<Autosave>,
+    mut combat_state: ResMut<State<CombatState>>,
+    mut escaped: ResMut<Escaped>,
+    mut acted: ResMut<Acted>,
+    mut gauge_query: Query<&mut AtbGauge>,
+) {
+    autosave.0 = true;
+    escaped.0 = false;
+    acted.0.clear();
+    for mut gauge in gauge_query.iter_mut() {
+        gauge.0 = 0.0;
+    }
+    combat_state
+        .set(config.turn_mode.turn_start())
+        .expect("Error setting the first CombatState");
+}
+
+fn stop_combat(
+    mut combat_state: ResMut<State<CombatState>>,
+    mut status_query: Query<&mut StatusEffects>,
+) {
+    for mut status_effects in status_query.iter_mut() {
+        status_effects.clear();
+    }
+    // The combat can end before the first CombatState is applied
+    if *combat_state.current() == CombatState::Inactive {
+        combat_state.clear_schedule();
+    } else {
+        combat_state
+            .overwrite_set(CombatState::Inactive)
+            .expect("Error setting CombatState::Inactive");
+    }
+}
+
+/// Fill the gauges of the living combatants, the party goes first if more than one is full
+fn fill_gauges(
+    time: Res<Time>,
+    config: Res<CombatConfig>,
+    mut combat_state: ResMut<State<CombatState>>,
+    mut gauge_query: Query<(&CombatStats, &mut AtbGauge, Option<&PartyMember>)>,
+) {
+    let mut player_ready = false;
+    let mut enemy_ready = false;
+    for (combat_stats, mut gauge, member) in gauge_query.iter_mut() {
+        if combat_stats.hp <= 0 {
+            continue;
+        }
+        gauge.0 += combat_stats.speed as f32 * config.gauge_fill_rate * time.delta_seconds();
+        gauge.0 = gauge.0.min(AtbGauge::FULL);
+        if gauge.is_full() {
+            match member {
+                Some(_) => player_ready = true,
+                None => enemy_ready = true,
+            }
+        }
+    }
+
+    if player_ready {
+        combat_state
+            .set(CombatState::PlayerTurn)
+            .expect("Error setting CombatState::PlayerTurn");
+    } else if enemy_ready {
+        combat_state
+            .set(CombatState::EnemyTurn)
+            .expect("Error setting CombatState::EnemyTurn");
+    }
 }
 
-fn despawn_enemy(mut commands: Commands, enemy_query: Query<Entity, With<Enemy>>) {
-    for ent in enemy_query.iter() {
-        commands.entity(ent).despawn_recursive();
+/// Choose the member that acts, the first living one in party order that has not acted
+/// or that has a full gauge. Its status effects tick, the turn is lost if it can not act
+#[allow(clippy::too_many_arguments)]
+fn start_player_turn(
+    config: Res<CombatConfig>,
+    party: Res<Party>,
+    mut acted: ResMut<Acted>,
+    mut active_member: ResMut<ActiveMember>,
+    mut combat_state: ResMut<State<CombatState>>,
+    mut acting_side: ResMut<ActingSide>,
+    mut log_event: EventWriter<BattleLogEvent>,
+    mut member_query: Query<
+        (&Name, &mut CombatStats, &mut StatusEffects, &mut AtbGauge),
+        With<PartyMember>,
+    >,
+) {
+    let member = party.members.iter().copied().find(|member| {
+        member_query
+            .get(*member)
+            .is_ok_and(|(_, combat_stats, _, gauge)| {
+                combat_stats.hp > 0
+                    && match config.turn_mode {
+                        TurnMode::TurnBased => !acted.0.contains(member),
+                        TurnMode::ActiveTime => gauge.is_full(),
+                    }
+            })
+    });
+    active_member.0 = member;
+    let member = match member {
+        Some(member) => member,
+        None => {
+            combat_state
+                .set(CombatState::EnemyTurn)
+                .expect("Error setting CombatState::EnemyTurn");
+            return;
+        }
+    };
+    acted.0.push(member);
+
+    let (name, mut combat_stats, mut status_effects, mut gauge) = member_query
+        .get_mut(member)
+        .expect("Can not get the active party member");
+    if start_turn(name, &mut combat_stats, &mut status_effects, &mut log_event) {
+        return;
     }
+    gauge.0 = 0.0;
+    *acting_side = ActingSide::Player;
+    combat_state
+        .set(CombatState::ResolveActions)
+        .expect("Error setting CombatState::ResolveActions");
 }
 
-fn combat_input(
-    keyboard: Res<Input<KeyCode>>,
+/// Every enemy that can act chooses its action with its `AiProfile`
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn enemy_turn(
+    config: Res<CombatConfig>,
     mut combat_event: EventWriter<CombatEvent>,
-    enemy_query: Query<Entity, With<Enemy>>,
-    player_query: Query<Entity, With<Player>>,
+    mut combat_state: ResMut<State<CombatState>>,
+    mut acting_side: ResMut<ActingSide>,
+    mut log_event: EventWriter<BattleLogEvent>,
+    skill_handle: Res<SkillDatabaseHandle>,
+    skill_databases: Res<Assets<SkillDatabase>>,
+    mut enemy_query: Query<
+        (
+            Entity,
+            &Name,
+            &mut CombatStats,
+            &mut StatusEffects,
+            &mut AtbGauge,
+            &AiProfile,
+        ),
+        (With<Enemy>, Without<PartyMember>),
+    >,
+    member_query: Query<(Entity, &CombatStats), (With<PartyMember>, Without<Enemy>)>,
 ) {
-    if keyboard.just_pressed(KeyCode::Space) {
-        // TODO Handle multiple enemys
-        let target = enemy_query
-            .get_single()
-            .expect("Can not get a target entity");
-        // TODO Handle multiple player entities
-        let emitter = player_query
-            .get_single()
-            .expect("Can not get Player entity");
-        combat_event.send(CombatEvent { target, emitter });
-        println!("Combat");
+    let skills = skill_databases
+        .get(&skill_handle.0)
+        .expect("Skill database not loaded 'CombatPlugin (enemy_turn)'");
+
+    let mut acting = Vec::new();
+    for (emitter, name, mut combat_stats, mut status_effects, mut gauge, _) in
+        enemy_query.iter_mut()
+    {
+        if combat_stats.hp <= 0 || (config.turn_mode == TurnMode::ActiveTime && !gauge.is_full()) {
+            continue;
+        }
+        gauge.0 = 0.0;
+        if start_turn(name, &mut combat_stats, &mut status_effects, &mut log_event) {
+            acting.push(emitter);
+        }
+    }
+
+    let members: Vec<_> = member_query
+        .iter()
+        .filter(|(_, combat_stats)| combat_stats.hp > 0)
+        .map(|(entity, combat_stats)| (entity, combat_stats.clone()))
+        .collect();
+    let enemies: Vec<_> = enemy_query
+        .iter()
+        .filter(|(_, _, combat_stats, _, _, _)| combat_stats.hp > 0)
+        .map(|(entity, _, combat_stats, _, _, _)| (entity, combat_stats.clone()))
+        .collect();
+    let mut rng = rand::thread_rng();
+    for emitter in acting {
+        let (_, name, combat_stats, _, _, ai_profile) = enemy_query
+            .get(emitter)
+            .expect("Can not get the acting enemy");
+        let (action, target) = match ai_profile.choose_action(combat_stats, skills, &mut rng) {
+            AiAction::Attack => (CombatAction::Attack, SkillTarget::Enemy),
+            AiAction::Skill(skill) => match skills.get(&skill) {
+                Some(skill_data) => (CombatAction::Skill(skill.clone()), skill_data.target),
+                None => {
+                    warn!("{name} chose the unknown skill {skill}, it attacks instead");
+                    (CombatAction::Attack, SkillTarget::Enemy)
+                }
+            },
+        };
+        let targets = ai_profile.choose_targets(target, emitter, &members, &enemies, &mut rng);
+        if targets.is_empty() {
+            continue;
+        }
+        combat_event.send(CombatEvent {
+            targets,
+            emitter,
+            action,
+        });
     }
+    *acting_side = ActingSide::Enemy;
+    combat_state
+        .set(CombatState::ResolveActions)
+        .expect("Error setting CombatState::ResolveActions");
 }
 
+#[allow(clippy::too_many_arguments)]
 fn process_combat(
     mut combat_event: EventReader<CombatEvent>,
-    mut combat_stats_query: Query<&mut CombatStats>,
+    mut hit_event: EventWriter<HitEvent>,
+    mut log_event: EventWriter<BattleLogEvent>,
+    mut escaped: ResMut<Escaped>,
+    damage_config: Res<DamageConfig>,
+    skill_handle: Res<SkillDatabaseHandle>,
+    skill_databases: Res<Assets<SkillDatabase>>,
+    item_handle: Res<ItemDatabaseHandle>,
+    item_databases: Res<Assets<ItemDatabase>>,
+    mut inventory: ResMut<Inventory>,
+    mut combat_stats_query: Query<(&mut CombatStats, &mut StatusEffects)>,
+    effective_query: Query<&EffectiveStats>,
+    affinity_query: Query<&Affinities>,
+    name_query: Query<&Name>,
+    enemy_query: Query<(Entity, Option<&BlocksEscape>), With<Enemy>>,
 ) {
+    let skills = skill_databases
+        .get(&skill_handle.0)
+        .expect("Skill database not loaded 'CombatPlugin (process_combat)'");
+    let items = item_databases
+        .get(&item_handle.0)
+        .expect("Item database not loaded 'CombatPlugin (process_combat)'");
+    let mut rng = rand::thread_rng();
     for event in combat_event.iter() {
-        let [emitter, mut target] = combat_stats_query
-            .get_many_mut([event.emitter, event.target])
-            .expect("Can not get any CombatStats");
-        println!("{emitter:#?}, {target:#?}");
-        target.hp -= i32::max(emitter.attack - target.defense, 0);
+        // The emitter could have died earlier on this turn
+        let (emitter, emitter_attack) = match combat_stats_query.get(event.emitter) {
+            Ok((combat_stats, status_effects)) if combat_stats.hp > 0 => (
+                combat_stats.clone(),
+                effective_of(&effective_query, event.emitter, combat_stats).attack
+                    + status_effects.attack(),
+            ),
+            _ => continue,
+        };
+        let emitter_name = name_of(&name_query, event.emitter);
+        let (effect, element) = match &event.action {
+            CombatAction::Attack => {
+                log_event.send(BattleLogEvent(format!("{emitter_name} attacks!")));
+                (SkillEffect::Damage(0), Element::Physical)
+            }
+            CombatAction::Flee => {
+                escaped.0 = try_to_flee(event.emitter, &combat_stats_query, &enemy_query);
+                log_event.send(BattleLogEvent(if escaped.0 {
+                    format!("{emitter_name} runs away!")
+                } else {
+                    format!("{emitter_name} could not escape!")
+                }));
+                continue;
+            }
+            CombatAction::Skill(name) => {
+                let skill = match skills.get(name) {
+                    Some(skill) => skill,
+                    None => {
+                        warn!("Unknown skill {name}");
+                        continue;
+                    }
+                };
+                let (mut emitter_stats, _) = combat_stats_query
+                    .get_mut(event.emitter)
+                    .expect("Can not get the emitter CombatStats");
+                if emitter_stats.mp < skill.mp_cost {
+                    continue;
+                }
+                emitter_stats.mp -= skill.mp_cost;
+                log_event.send(BattleLogEvent(format!("{emitter_name} casts {name}!")));
+                (skill.effect.clone(), skill.element)
+            }
+            CombatAction::Item(name) => {
+                let item = match items.get(name) {
+                    Some(item) => item,
+                    None => {
+                        warn!("Unknown item {name}");
+                        continue;
+                    }
+                };
+                if !inventory.remove_item(name) {
+                    continue;
+                }
+                log_event.send(BattleLogEvent(format!("{emitter_name} uses {name}!")));
+                (item.effect.clone(), Element::Physical)
+            }
+        };
+
+        for target in event.targets.iter() {
+            let (mut target_stats, mut target_effects) = match combat_stats_query.get_mut(*target) {
+                // The target could have died earlier on this turn
+                Ok((combat_stats, status_effects)) if effect.can_target(&combat_stats) => {
+                    (combat_stats, status_effects)
+                }
+                _ => continue,
+            };
+            let target_name = name_of(&name_query, *target);
+            match effect {
+                SkillEffect::Damage(power) => {
+                    let input = DamageInput {
+                        power,
+                        attack: emitter_attack,
+                        defense: effective_of(&effective_query, *target, &target_stats).defense
+                            + target_effects.defense(),
+                        accuracy: emitter.accuracy,
+                        evasion: target_stats.evasion,
+                        affinity: affinity_query
+                            .get(*target)
+                            .map(|affinities| affinities.get(element))
+                            .unwrap_or_default(),
+                    };
+                    let (damage, critical) = match roll_damage(&damage_config, &input, &mut rng) {
+                        DamageOutcome::Hit { damage, critical } => (damage, critical),
+                        DamageOutcome::Miss => {
+                            log_event.send(BattleLogEvent(format!(
+                                "{emitter_name} misses {target_name}."
+                            )));
+                            hit_event.send(HitEvent {
+                                target: *target,
+                                outcome: HitOutcome::Miss,
+                            });
+                            continue;
+                        }
+                    };
+                    if critical {
+                        log_event.send(BattleLogEvent(String::from("Critical hit!")));
+                    }
+                    if input.affinity == Affinity::Weak {
+                        log_event.send(BattleLogEvent(format!(
+                            "{emitter_name} hits a weakness of {target_name}!"
+                        )));
+                    }
+                    // Absorbed damage heals the target
+                    let hp = target_stats.hp;
+                    target_stats.hp = i32::min(hp - damage, target_stats.max_hp);
+                    let outcome = if damage > 0 {
+                        target_effects.remove(StatusKind::Sleep);
+                        log_event.send(BattleLogEvent(format!(
+                            "{target_name} takes {damage} damage."
+                        )));
+                        HitOutcome::Damage {
+                            amount: damage,
+                            critical,
+                        }
+                    } else if damage < 0 {
+                        log_event.send(BattleLogEvent(format!(
+                            "{target_name} absorbs {} HP.",
+                            target_stats.hp - hp
+                        )));
+                        HitOutcome::Heal(target_stats.hp - hp)
+                    } else {
+                        log_event.send(BattleLogEvent(format!(
+                            "It has no effect on {target_name}."
+                        )));
+                        HitOutcome::Damage {
+                            amount: 0,
+                            critical,
+                        }
+                    };
+                    hit_event.send(HitEvent {
+                        target: *target,
+                        outcome,
+                    });
+                    if target_stats.hp <= 0 {
+                        log_event.send(BattleLogEvent(format!("{target_name} is defeated!")));
+                    }
+                }
+                _ => {
+                    // A revived target starts from 0 hp
+                    let (hp, mp) = (i32::max(target_stats.hp, 0), target_stats.mp);
+                    effect.apply(&mut target_stats, &mut target_effects);
+                    if let Some(message) = effect_message(
+                        &effect,
+                        &target_name,
+                        target_stats.hp - hp,
+                        target_stats.mp - mp,
+                    ) {
+                        log_event.send(BattleLogEvent(message));
+                    }
+                    if target_stats.hp > hp {
+                        hit_event.send(HitEvent {
+                            target: *target,
+                            outcome: HitOutcome::Heal(target_stats.hp - hp),
+                        });
+                    }
+                    if target_stats.mp > mp {
+                        hit_event.send(HitEvent {
+                            target: *target,
+                            outcome: HitOutcome::RestoreMp(target_stats.mp - mp),
+                        });
+                    }
+                }
+            }
+        }
     }
 }
 
-fn end_combat(
-    mut state: ResMut<State<AppState>>,
-    enemy_stats_query: Query<&CombatStats, With<Enemy>>,
-) {
-    // TODO Handle multiple enemys and player losing
-    for combat_stats in enemy_stats_query.iter() {
-        if combat_stats.hp <= 0 {
-            state.pop().expect("Error poping Combat state");
+/// Combatants without `EffectiveStats` use their base stats
+fn effective_of(
+    effective_query: &Query<&EffectiveStats>,
+    entity: Entity,
+    combat_stats: &CombatStats,
+) -> EffectiveStats {
+    effective_query
+        .get(entity)
+        .cloned()
+        .unwrap_or_else(|_| EffectiveStats::from(combat_stats))
+}
+
+/// Line of the battle log for the effects that are not damage
+fn effect_message(
+    effect: &SkillEffect,
+    target_name: &str,
+    hp_gained: i32,
+    mp_gained: i32,
+) -> Option<String> {
+    let message = match effect {
+        SkillEffect::Damage(_) => return None,
+        SkillEffect::Heal(_) => format!("{target_name} recovers {hp_gained} HP."),
+        SkillEffect::RestoreMp(_) => format!("{target_name} recovers {mp_gained} MP."),
+        SkillEffect::Inflict(status_effect) => {
+            format!("{target_name} is affected by {:?}.", status_effect.kind)
+        }
+        SkillEffect::RemoveStatus(kind) => format!("{target_name} is cured of {kind:?}."),
+        SkillEffect::Revive(_) => format!("{target_name} is revived!"),
+    };
+    Some(message)
+}
+
+/// Tick the status effects at the start of the turn of a combatant and log what happened,
+/// returns false when it can not act
+fn start_turn(
+    name: &Name,
+    combat_stats: &mut CombatStats,
+    status_effects: &mut StatusEffects,
+    log_event: &mut EventWriter<BattleLogEvent>,
+) -> bool {
+    let hp = combat_stats.hp;
+    let can_act = status_effects.start_turn(combat_stats);
+    if combat_stats.hp < hp {
+        log_event.send(BattleLogEvent(format!(
+            "{name} takes {} poison damage.",
+            hp - combat_stats.hp
+        )));
+    }
+    if combat_stats.hp <= 0 {
+        log_event.send(BattleLogEvent(format!("{name} is defeated!")));
+    } else if !can_act {
+        log_event.send(BattleLogEvent(format!("{name} can not move!")));
+    }
+    can_act
+}
+
+fn name_of(name_query: &Query<&Name>, entity: Entity) -> String {
+    name_query
+        .get(entity)
+        .map(|name| name.to_string())
+        .unwrap_or_default()
+}
+
+/// The chance to flee depends on the speed of the runner against the fastest enemy alive
+fn try_to_flee(
+    runner: Entity,
+    combat_stats_query: &Query<(&mut CombatStats, &mut StatusEffects)>,
+    enemy_query: &Query<(Entity, Option<&BlocksEscape>), With<Enemy>>,
+) -> bool {
+    let runner_speed = match combat_stats_query.get(runner) {
+        Ok((combat_stats, _)) => combat_stats.speed,
+        Err(_) => return false,
+    };
+    let mut enemy_speed = i32::MIN;
+    for (enemy, blocks_escape) in enemy_query.iter() {
+        let combat_stats = match combat_stats_query.get(enemy) {
+            Ok((combat_stats, _)) if combat_stats.hp > 0 => combat_stats,
+            _ => continue,
+        };
+        if blocks_escape.is_some() {
+            return false;
         }
+        enemy_speed = enemy_speed.max(combat_stats.speed);
     }
+    rand::thread_rng().gen::<f32>() < flee_chance(runner_speed, enemy_speed)
 }
 
-fn force_end_combat(mut keyboard: ResMut<Input<KeyCode>>, mut state: ResMut<State<AppState>>) {
-    if keyboard.just_pressed(KeyCode::Q) {
-        keyboard.reset(KeyCode::Q);
+fn flee_chance(runner_speed: i32, enemy_speed: i32) -> f32 {
+    let speed_difference = runner_speed.saturating_sub(enemy_speed) as f32;
+    (FLEE_BASE_CHANCE + speed_difference * FLEE_CHANCE_PER_SPEED).clamp(0.1, 0.95)
+}
+
+/// On turn based combat the enemies act after every party member, after that the turn ends.
+/// The turn ends early when every enemy is dead
+#[allow(clippy::too_many_arguments)]
+fn end_resolution(
+    config: Res<CombatConfig>,
+    acting_side: Res<ActingSide>,
+    escaped: Res<Escaped>,
+    party: Res<Party>,
+    acted: Res<Acted>,
+    mut combat_state: ResMut<State<CombatState>>,
+    member_query: Query<&CombatStats, With<PartyMember>>,
+    enemy_query: Query<&CombatStats, With<Enemy>>,
+) {
+    let enemies_alive = enemy_query.iter().any(|combat_stats| combat_stats.hp > 0);
+    let next_state = match (config.turn_mode, *acting_side) {
+        (TurnMode::TurnBased, ActingSide::Player) if !escaped.0 && enemies_alive => {
+            let waiting = party.members.iter().any(|member| {
+                !acted.0.contains(member)
+                    && member_query
+                        .get(*member)
+                        .is_ok_and(|combat_stats| combat_stats.hp > 0)
+            });
+            if waiting {
+                CombatState::PlayerTurn
+            } else {
+                CombatState::EnemyTurn
+            }
+        }
+        _ => CombatState::TurnEnd,
+    };
+    combat_state
+        .set(next_state)
+        .expect("Error setting the next CombatState");
+}
+
+/// The game is over when every party member is dead, without party members the combat just ends
+#[allow(clippy::too_many_arguments)]
+fn end_combat(
+    mut commands: Commands,
+    config: Res<CombatConfig>,
+    mut state: ResMut<State<AppState>>,
+    mut combat_state: ResMut<State<CombatState>>,
+    escaped: Res<Escaped>,
+    mut acted: ResMut<Acted>,
+    enemy_stats_query: Query<&CombatStats, With<Enemy>>,
+    member_stats_query: Query<&CombatStats, With<PartyMember>>,
+) {
+    if !member_stats_query.is_empty()
+        && member_stats_query
+            .iter()
+            .all(|combat_stats| combat_stats.hp <= 0)
+    {
+        commands.insert_resource(FadeoutConfigResource {
+            fadeout_duration: 1.5,
+            next_state: Some(AppState::GameOver),
+            position: Vec3::ZERO,
+        });
+        state
+            .set(AppState::Fadeout)
+            .expect("Error setting state to App::Fadeout 'Combat plugin'");
+    } else if escaped.0 || member_stats_query.is_empty() {
         state.pop().expect("Error poping Combat state");
+    } else if !enemy_stats_query.is_empty()
+        && enemy_stats_query
+            .iter()
+            .all(|combat_stats| combat_stats.hp <= 0)
+    {
+        combat_state
+            .set(CombatState::Victory)
+            .expect("Error setting CombatState::Victory");
+    } else {
+        acted.0.clear();
+        combat_state
+            .set(config.turn_mode.turn_start())
+            .expect("Error setting the next CombatState");
     }
 }
@@ -1,4 +1,4 @@
-use bevy::prelude::Component;
+use bevy::prelude::{Component, Vec2};
 use bevy_inspector_egui::Inspectable;
 
 #[derive(Debug, Component)]
@@ -7,13 +7,67 @@ pub struct Speed(pub f32);
 #[derive(Debug, Component)]
 pub struct Collider;
 
+/// Tile where random encounters happen, it belongs to a region of the `EncounterDatabase`
 #[derive(Debug, Component)]
-pub struct EncounterSpawn;
+pub struct EncounterSpawn {
+    pub region: String,
+}
 
-#[derive(Debug, Component, Inspectable)]
+/// Tile that starts a fixed encounter of the `EncounterDatabase` when the player steps on it
+#[derive(Debug, Component)]
+pub struct EncounterTrigger {
+    pub encounter: String,
+}
+
+/// Moves the player to the destination when it steps on it
+#[derive(Debug, Component)]
+pub struct Warp {
+    pub destination: Vec2,
+}
+
+/// Extra tags of a tile, written on the legend of the map
+#[derive(Debug, Clone, Component, Inspectable)]
+pub struct TileTags(pub Vec<String>);
+
+#[derive(Debug, Clone, Component, Inspectable)]
 pub struct CombatStats {
     pub hp: i32,
     pub max_hp: i32,
+    pub mp: i32,
+    pub max_mp: i32,
+    pub attack: i32,
+    pub defense: i32,
+    pub speed: i32,
+    /// Hit chance in percent
+    pub accuracy: i32,
+    /// Percent subtracted from the accuracy of the attackers
+    pub evasion: i32,
+}
+
+/// Stats used on combat, the base `CombatStats` plus the modifiers of the equipment
+#[derive(Debug, Clone, Default, Component, Inspectable)]
+pub struct EffectiveStats {
     pub attack: i32,
     pub defense: i32,
 }
+
+impl From<&CombatStats> for EffectiveStats {
+    fn from(combat_stats: &CombatStats) -> Self {
+        Self {
+            attack: combat_stats.attack,
+            defense: combat_stats.defense,
+        }
+    }
+}
+
+/// Active time battle gauge, the combatant can act when is full
+#[derive(Debug, Default, Component, Inspectable)]
+pub struct AtbGauge(pub f32);
+
+impl AtbGauge {
+    pub const FULL: f32 = 100.0;
+
+    pub fn is_full(&self) -> bool {
+        self.0 >= Self::FULL
+    }
+}
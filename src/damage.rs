@@ -0,0 +1,199 @@
+use bevy_inspector_egui::Inspectable;
+use rand::Rng;
+
+use crate::element::Affinity;
+
+/// Tunables of the damage formula
+#[derive(Debug, Clone, Inspectable)]
+pub struct DamageConfig {
+    /// Random spread of the damage, 0.1 goes from 90% to 110%
+    pub variance: f32,
+    /// Probability of a critical hit, from 0.0 to 1.0
+    pub critical_chance: f32,
+    pub critical_multiplier: f32,
+    /// Damage dealt by any hit after the affinity, immune targets take none
+    /// and absorbing ones heal at least this much
+    pub min_damage: i32,
+    /// Hit chance bounds after accuracy and evasion
+    pub min_hit_chance: f32,
+    pub max_hit_chance: f32,
+}
+
+impl Default for DamageConfig {
+    fn default() -> Self {
+        Self {
+            variance: 0.1,
+            critical_chance: 0.05,
+            critical_multiplier: 2.0,
+            min_damage: 1,
+            min_hit_chance: 0.05,
+            max_hit_chance: 1.0,
+        }
+    }
+}
+
+/// Everything the formula needs to know about the attacker and the target
+#[derive(Debug, Clone)]
+pub struct DamageInput {
+    /// Power of the skill, 0 for plain attacks
+    pub power: i32,
+    pub attack: i32,
+    pub defense: i32,
+    /// Hit chance in percent
+    pub accuracy: i32,
+    /// Percent subtracted from the accuracy of the attacker
+    pub evasion: i32,
+    pub affinity: Affinity,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DamageOutcome {
+    Miss,
+    /// The damage is negative when the target absorbs it
+    Hit {
+        damage: i32,
+        critical: bool,
+    },
+}
+
+/// Roll the damage of a hit, the randomness only comes from `rng`
+pub fn roll_damage(
+    config: &DamageConfig,
+    input: &DamageInput,
+    rng: &mut impl Rng,
+) -> DamageOutcome {
+    let hit_chance = ((input.accuracy - input.evasion) as f32 / 100.0)
+        .clamp(config.min_hit_chance, config.max_hit_chance);
+    if rng.gen::<f32>() >= hit_chance {
+        return DamageOutcome::Miss;
+    }
+
+    let base = (input.power + input.attack - input.defense) as f32;
+    let spread = 1.0 + config.variance * rng.gen_range(-1.0..=1.0);
+    let critical = rng.gen::<f32>() < config.critical_chance;
+    let multiplier = if critical {
+        config.critical_multiplier
+    } else {
+        1.0
+    };
+    let damage = ((base * spread * multiplier).round() as i32).max(config.min_damage);
+    let damage = match input.affinity {
+        Affinity::Immune | Affinity::Absorb => input.affinity.scale(damage),
+        _ => input.affinity.scale(damage).max(config.min_damage),
+    };
+    DamageOutcome::Hit { damage, critical }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::*;
+
+    const ROLLS: usize = 2000;
+
+    fn input(attack: i32, defense: i32, affinity: Affinity) -> DamageInput {
+        DamageInput {
+            power: 0,
+            attack,
+            defense,
+            accuracy: 100,
+            evasion: 0,
+            affinity,
+        }
+    }
+
+    /// Config without randomness on the damage
+    fn fixed_config() -> DamageConfig {
+        DamageConfig {
+            variance: 0.0,
+            critical_chance: 0.0,
+            ..Default::default()
+        }
+    }
+
+    fn hits(config: &DamageConfig, input: &DamageInput) -> usize {
+        let mut rng = StdRng::seed_from_u64(7);
+        (0..ROLLS)
+            .filter(|_| roll_damage(config, input, &mut rng) != DamageOutcome::Miss)
+            .count()
+    }
+
+    fn damage(config: &DamageConfig, input: &DamageInput, rng: &mut StdRng) -> i32 {
+        match roll_damage(config, input, rng) {
+            DamageOutcome::Hit { damage, .. } => damage,
+            DamageOutcome::Miss => panic!("Unexpected miss"),
+        }
+    }
+
+    #[test]
+    fn evasion_can_not_go_under_min_hit_chance() {
+        let mut evaded = input(10, 0, Affinity::Normal);
+        evaded.evasion = 500;
+        let config = DamageConfig {
+            min_hit_chance: 0.0,
+            ..fixed_config()
+        };
+        assert_eq!(hits(&config, &evaded), 0);
+
+        let hit_count = hits(&fixed_config(), &evaded);
+        assert!((50..150).contains(&hit_count), "{hit_count} hits");
+    }
+
+    #[test]
+    fn accuracy_is_clamped_to_max_hit_chance() {
+        let mut accurate = input(10, 0, Affinity::Normal);
+        accurate.accuracy = 500;
+        assert_eq!(hits(&fixed_config(), &accurate), ROLLS);
+
+        let config = DamageConfig {
+            max_hit_chance: 0.5,
+            ..fixed_config()
+        };
+        let hit_count = hits(&config, &accurate);
+        assert!((900..1100).contains(&hit_count), "{hit_count} hits");
+    }
+
+    #[test]
+    fn critical_hits_use_the_multiplier() {
+        let config = DamageConfig {
+            critical_chance: 1.0,
+            critical_multiplier: 3.0,
+            ..fixed_config()
+        };
+        let mut rng = StdRng::seed_from_u64(7);
+        assert_eq!(
+            roll_damage(&config, &input(20, 5, Affinity::Normal), &mut rng),
+            DamageOutcome::Hit {
+                damage: 45,
+                critical: true
+            }
+        );
+    }
+
+    #[test]
+    fn variance_stays_inside_its_range() {
+        let config = DamageConfig {
+            variance: 0.1,
+            ..fixed_config()
+        };
+        let mut rng = StdRng::seed_from_u64(7);
+        let rolls: Vec<_> = (0..ROLLS)
+            .map(|_| damage(&config, &input(100, 0, Affinity::Normal), &mut rng))
+            .collect();
+        assert!(rolls.iter().all(|damage| (90..=110).contains(damage)));
+        assert!(rolls.iter().any(|damage| *damage != 100));
+    }
+
+    #[test]
+    fn hits_deal_at_least_min_damage_after_the_affinity() {
+        let config = fixed_config();
+        let mut rng = StdRng::seed_from_u64(7);
+        let weak_hit = |affinity| input(1, 50, affinity);
+        assert_eq!(damage(&config, &weak_hit(Affinity::Normal), &mut rng), 1);
+        assert_eq!(damage(&config, &weak_hit(Affinity::Weak), &mut rng), 2);
+        assert_eq!(damage(&config, &weak_hit(Affinity::Resist), &mut rng), 1);
+        assert_eq!(damage(&config, &weak_hit(Affinity::Immune), &mut rng), 0);
+        assert_eq!(damage(&config, &weak_hit(Affinity::Absorb), &mut rng), -1);
+    }
+}
@@ -1,20 +1,67 @@
+// `FrameTimeDiagnosticsPlugin` stays imported for the commented plugin below
+#[allow(unused_imports)]
 use bevy::diagnostic::{FrameTimeDiagnosticsPlugin, LogDiagnosticsPlugin};
 use bevy::prelude::*;
-use bevy_inspector_egui::{RegisterInspectable, WorldInspectorPlugin};
+use bevy_inspector_egui::{InspectorPlugin, RegisterInspectable, WorldInspectorPlugin};
 
-use crate::combat_plugin::Enemy;
-use crate::common_component::CombatStats;
+use crate::combat_plugin::CombatConfig;
+use crate::common_component::{AtbGauge, CombatStats, EffectiveStats, TileTags};
+use crate::damage::DamageConfig;
+use crate::element::Affinities;
+use crate::enemy_plugin::{BlocksEscape, Enemy, Reward};
+use crate::equipment_plugin::Equipment;
+use crate::experience_plugin::Experience;
+use crate::party_plugin::{PartyEvent, PartyMember};
 use crate::player_plugin::{CombatTimer, Player};
+use crate::skill_plugin::Skills;
+use crate::status_effect::StatusEffects;
+use crate::AppState;
 
 pub struct DebugPlugin;
 impl Plugin for DebugPlugin {
     fn build(&self, app: &mut App) {
         app.add_plugin(WorldInspectorPlugin::new())
+            .add_plugin(InspectorPlugin::<CombatConfig>::new())
+            .add_plugin(InspectorPlugin::<DamageConfig>::new())
             .add_plugin(LogDiagnosticsPlugin::default())
             // .add_plugin(FrameTimeDiagnosticsPlugin::default())
             .register_inspectable::<Player>()
+            .register_inspectable::<PartyMember>()
             .register_inspectable::<CombatTimer>()
             .register_inspectable::<CombatStats>()
-            .register_inspectable::<Enemy>();
+            .register_inspectable::<EffectiveStats>()
+            .register_inspectable::<Equipment>()
+            .register_inspectable::<AtbGauge>()
+            .register_inspectable::<Enemy>()
+            .register_inspectable::<Reward>()
+            .register_inspectable::<Experience>()
+            .register_inspectable::<BlocksEscape>()
+            .register_inspectable::<StatusEffects>()
+            .register_inspectable::<Affinities>()
+            .register_inspectable::<Skills>()
+            .register_inspectable::<TileTags>();
+
+        app.add_system_set(SystemSet::on_update(AppState::Combat).with_system(force_end_combat))
+            .add_system_set(SystemSet::on_update(AppState::OverWorld).with_system(change_party));
+    }
+}
+
+/// Leave the combat without any rules
+fn force_end_combat(mut keyboard: ResMut<Input<KeyCode>>, mut state: ResMut<State<AppState>>) {
+    if keyboard.just_pressed(KeyCode::Q) {
+        keyboard.reset(KeyCode::Q);
+        state.pop().expect("Error poping Combat state");
+    }
+}
+
+/// Add or remove a member of the party
+fn change_party(mut keyboard: ResMut<Input<KeyCode>>, mut party_event: EventWriter<PartyEvent>) {
+    if keyboard.just_pressed(KeyCode::J) {
+        keyboard.reset(KeyCode::J);
+        party_event.send(PartyEvent::Join(String::from("Cobalt")));
+    }
+    if keyboard.just_pressed(KeyCode::K) {
+        keyboard.reset(KeyCode::K);
+        party_event.send(PartyEvent::Leave(String::from("Cobalt")));
     }
 }
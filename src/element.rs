@@ -0,0 +1,61 @@
+use bevy::prelude::Component;
+use bevy_inspector_egui::Inspectable;
+use serde::Deserialize;
+
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Deserialize, Inspectable)]
+pub enum Element {
+    /// Plain attacks and skills without an element
+    #[default]
+    Physical,
+    Fire,
+    Ice,
+    Thunder,
+    Earth,
+}
+
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Deserialize, Inspectable)]
+pub enum Affinity {
+    #[default]
+    Normal,
+    /// Double damage
+    Weak,
+    /// Half damage
+    Resist,
+    /// No damage
+    Immune,
+    /// The damage heals instead
+    Absorb,
+}
+
+impl Affinity {
+    /// Damage after the affinity, negative when it heals
+    pub fn scale(&self, damage: i32) -> i32 {
+        match self {
+            Affinity::Normal => damage,
+            Affinity::Weak => damage * 2,
+            Affinity::Resist => damage / 2,
+            Affinity::Immune => 0,
+            Affinity::Absorb => -damage,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Inspectable)]
+pub struct ElementAffinity {
+    pub element: Element,
+    pub affinity: Affinity,
+}
+
+/// How a combatant takes damage from each element, missing elements are `Affinity::Normal`
+#[derive(Debug, Clone, Default, Component, Inspectable)]
+pub struct Affinities(pub Vec<ElementAffinity>);
+
+impl Affinities {
+    pub fn get(&self, element: Element) -> Affinity {
+        self.0
+            .iter()
+            .find(|entry| entry.element == element)
+            .map(|entry| entry.affinity)
+            .unwrap_or_default()
+    }
+}
@@ -0,0 +1,99 @@
+use anyhow::anyhow;
+use bevy::{prelude::Handle, reflect::TypeUuid};
+use rand::{distributions::WeightedIndex, prelude::Distribution, Rng};
+use serde::Deserialize;
+
+/// Group of enemies that can be found together
+#[derive(Debug, Clone, Deserialize)]
+pub struct FormationData {
+    pub weight: u32,
+    /// Names of the enemies on the `EnemyDatabase`, from left to right
+    pub enemies: Vec<String>,
+}
+
+/// Area of the map where random encounters happen, as written in the encounter database
+#[derive(Debug, Clone, Deserialize)]
+pub struct RegionData {
+    pub name: String,
+    /// Distance walked inside the region between encounters, rolled after each one
+    pub min_range: f32,
+    pub max_range: f32,
+    pub formations: Vec<FormationData>,
+}
+
+impl RegionData {
+    /// The chance of each formation is its weight over the total weight
+    pub fn choose_formation(&self, rng: &mut impl Rng) -> Option<&FormationData> {
+        match WeightedIndex::new(self.formations.iter().map(|formation| formation.weight)) {
+            Ok(index) => Some(&self.formations[index.sample(rng)]),
+            Err(_) => None,
+        }
+    }
+}
+
+/// Encounter started by a trigger on the map, like a boss
+#[derive(Debug, Clone, Deserialize)]
+pub struct FixedEncounterData {
+    pub name: String,
+    pub enemies: Vec<String>,
+    /// Story flag set on victory, the encounter doesn't happen again once it is set
+    pub flag: String,
+    /// Characters of the `PartyDatabase` that join the party on victory
+    #[serde(default)]
+    pub joins: Vec<String>,
+    /// Party members that leave the party on victory
+    #[serde(default)]
+    pub leaves: Vec<String>,
+    #[serde(default)]
+    pub can_flee: bool,
+    /// Path of the music played during the combat, inside the assets folder
+    #[serde(default)]
+    pub music: Option<String>,
+    /// Color behind the combatants, the combat screen is black without it
+    #[serde(default)]
+    pub background: Option<(f32, f32, f32)>,
+}
+
+#[derive(Debug, Deserialize, TypeUuid)]
+#[uuid = "4f8b2d6a-93c1-4e7f-a5d0-6b1e8c3f9a27"]
+pub struct EncounterDatabase {
+    pub regions: Vec<RegionData>,
+    #[serde(default)]
+    pub fixed_encounters: Vec<FixedEncounterData>,
+}
+
+impl EncounterDatabase {
+    pub fn get(&self, name: &str) -> Option<&RegionData> {
+        self.regions.iter().find(|region| region.name == name)
+    }
+
+    pub fn get_fixed(&self, name: &str) -> Option<&FixedEncounterData> {
+        self.fixed_encounters
+            .iter()
+            .find(|encounter| encounter.name == name)
+    }
+
+    /// The distance to the next encounter is rolled between the `min_range` and the `max_range`
+    pub fn validate(&self) -> Result<(), anyhow::Error> {
+        match self
+            .regions
+            .iter()
+            .find(|region| region.min_range > region.max_range)
+        {
+            Some(region) => Err(anyhow!(
+                "The min_range of the region {} is over its max_range",
+                region.name
+            )),
+            None => Ok(()),
+        }
+    }
+}
+
+pub struct EncounterDatabaseHandle(pub Handle<EncounterDatabase>);
+
+/// Enemies of the next combat, without it the `EnemyPlugin` spawns a random formation
+#[derive(Debug, Clone)]
+pub struct NextEncounter {
+    pub enemies: Vec<String>,
+    pub can_flee: bool,
+}
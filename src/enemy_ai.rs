@@ -0,0 +1,248 @@
+use bevy::prelude::{Component, Entity};
+use rand::{distributions::WeightedIndex, prelude::Distribution, seq::SliceRandom, Rng};
+use serde::Deserialize;
+
+use crate::{
+    common_component::CombatStats,
+    skill_plugin::{SkillDatabase, SkillTarget},
+};
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub enum AiAction {
+    Attack,
+    /// Use the skill with this name, it is skipped without enough mp
+    Skill(String),
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub enum AiCondition {
+    /// Hp under this fraction of the max hp, from 0.0 to 1.0
+    HpBelow(f32),
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct AiRule {
+    pub condition: AiCondition,
+    pub action: AiAction,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct AiChoice {
+    pub weight: u32,
+    pub action: AiAction,
+}
+
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Deserialize)]
+pub enum AiTargeting {
+    #[default]
+    Random,
+    /// The target with the lowest hp
+    Weakest,
+}
+
+/// How an enemy chooses its actions, the default profile always attacks a random target
+#[derive(Debug, Clone, Default, Deserialize, Component)]
+#[serde(default)]
+pub struct AiProfile {
+    /// Checked in order, the first rule that matches chooses the action
+    pub rules: Vec<AiRule>,
+    /// Weighted choices used when no rule matches, attack when it is empty
+    pub choices: Vec<AiChoice>,
+    pub targeting: AiTargeting,
+}
+
+impl AiProfile {
+    pub fn choose_action(
+        &self,
+        combat_stats: &CombatStats,
+        skills: &SkillDatabase,
+        rng: &mut impl Rng,
+    ) -> AiAction {
+        let usable = |action: &AiAction| match action {
+            AiAction::Attack => true,
+            AiAction::Skill(name) => skills
+                .get(name)
+                .is_some_and(|skill| combat_stats.mp >= skill.mp_cost),
+        };
+
+        let rule = self.rules.iter().find(|rule| {
+            let matches = match rule.condition {
+                AiCondition::HpBelow(fraction) => {
+                    (combat_stats.hp as f32) < combat_stats.max_hp as f32 * fraction
+                }
+            };
+            matches && usable(&rule.action)
+        });
+        if let Some(rule) = rule {
+            return rule.action.clone();
+        }
+
+        let choices: Vec<_> = self
+            .choices
+            .iter()
+            .filter(|choice| usable(&choice.action))
+            .collect();
+        match WeightedIndex::new(choices.iter().map(|choice| choice.weight)) {
+            Ok(index) => choices[index.sample(rng)].action.clone(),
+            Err(_) => AiAction::Attack,
+        }
+    }
+
+    /// Targets of an action that follows `target`, `opponents` and `allies` are the combatants
+    /// of each side from the point of view of the `user`, the fallen ones are never picked
+    pub fn choose_targets(
+        &self,
+        target: SkillTarget,
+        user: Entity,
+        opponents: &[(Entity, CombatStats)],
+        allies: &[(Entity, CombatStats)],
+        rng: &mut impl Rng,
+    ) -> Vec<Entity> {
+        let pick = |candidates: &[(Entity, CombatStats)], rng: &mut _| {
+            let living: Vec<_> = candidates
+                .iter()
+                .filter(|(_, combat_stats)| combat_stats.hp > 0)
+                .collect();
+            let candidate = match self.targeting {
+                AiTargeting::Random => living.choose(rng).copied(),
+                AiTargeting::Weakest => living
+                    .into_iter()
+                    .min_by_key(|(_, combat_stats)| combat_stats.hp),
+            };
+            candidate.map(|(entity, _)| *entity).into_iter().collect()
+        };
+        match target {
+            SkillTarget::Enemy => pick(opponents, rng),
+            SkillTarget::AllEnemies => opponents.iter().map(|(entity, _)| *entity).collect(),
+            SkillTarget::User => vec![user],
+            SkillTarget::Ally => pick(allies, rng),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::*;
+    use crate::skill_plugin::{SkillData, SkillEffect};
+
+    const ROLLS: usize = 200;
+
+    fn stats(hp: i32, mp: i32) -> CombatStats {
+        CombatStats {
+            hp,
+            max_hp: 100,
+            mp,
+            max_mp: 20,
+            attack: 10,
+            defense: 5,
+            speed: 5,
+            accuracy: 90,
+            evasion: 5,
+        }
+    }
+
+    fn skills() -> SkillDatabase {
+        let skill = |name: &str, mp_cost, effect| SkillData {
+            name: name.to_string(),
+            mp_cost,
+            element: Default::default(),
+            effect,
+            target: SkillTarget::Enemy,
+        };
+        SkillDatabase {
+            skills: vec![
+                skill("Fire", 5, SkillEffect::Damage(10)),
+                skill("Cure", 3, SkillEffect::Heal(20)),
+            ],
+        }
+    }
+
+    fn skill(name: &str) -> AiAction {
+        AiAction::Skill(name.to_string())
+    }
+
+    /// Heals under half hp, otherwise casts fire or attacks
+    fn profile() -> AiProfile {
+        AiProfile {
+            rules: vec![AiRule {
+                condition: AiCondition::HpBelow(0.5),
+                action: skill("Cure"),
+            }],
+            choices: vec![
+                AiChoice {
+                    weight: 3,
+                    action: skill("Fire"),
+                },
+                AiChoice {
+                    weight: 1,
+                    action: AiAction::Attack,
+                },
+            ],
+            targeting: AiTargeting::Random,
+        }
+    }
+
+    fn actions(profile: &AiProfile, combat_stats: &CombatStats) -> Vec<AiAction> {
+        let skills = skills();
+        let mut rng = StdRng::seed_from_u64(7);
+        (0..ROLLS)
+            .map(|_| profile.choose_action(combat_stats, &skills, &mut rng))
+            .collect()
+    }
+
+    #[test]
+    fn rules_take_priority_over_choices() {
+        let chosen = actions(&profile(), &stats(40, 20));
+        assert!(chosen.iter().all(|action| *action == skill("Cure")));
+
+        let chosen = actions(&profile(), &stats(60, 20));
+        assert!(chosen.contains(&skill("Fire")));
+        assert!(chosen.contains(&AiAction::Attack));
+        assert!(!chosen.contains(&skill("Cure")));
+    }
+
+    #[test]
+    fn unaffordable_skills_fall_back_to_other_actions() {
+        // Cure is affordable but not fire
+        let chosen = actions(&profile(), &stats(60, 4));
+        assert!(chosen.iter().all(|action| *action == AiAction::Attack));
+
+        // Without mp for the rule the choices are used
+        let chosen = actions(&profile(), &stats(40, 2));
+        assert!(chosen.iter().all(|action| *action == AiAction::Attack));
+
+        let unknown = AiProfile {
+            choices: vec![AiChoice {
+                weight: 1,
+                action: skill("Meteor"),
+            }],
+            ..Default::default()
+        };
+        let chosen = actions(&unknown, &stats(60, 20));
+        assert!(chosen.iter().all(|action| *action == AiAction::Attack));
+    }
+
+    #[test]
+    fn weakest_targets_the_living_opponent_with_lowest_hp() {
+        let profile = AiProfile {
+            targeting: AiTargeting::Weakest,
+            ..Default::default()
+        };
+        let user = Entity::from_raw(0);
+        let opponents = [
+            (Entity::from_raw(1), stats(30, 0)),
+            (Entity::from_raw(2), stats(0, 0)),
+            (Entity::from_raw(3), stats(12, 0)),
+            (Entity::from_raw(4), stats(50, 0)),
+        ];
+        let mut rng = StdRng::seed_from_u64(7);
+        let targets = profile.choose_targets(SkillTarget::Enemy, user, &opponents, &[], &mut rng);
+        assert_eq!(targets, vec![Entity::from_raw(3)]);
+
+        let fallen = [(Entity::from_raw(2), stats(0, 0))];
+        let targets = profile.choose_targets(SkillTarget::Enemy, user, &fallen, &[], &mut rng);
+        assert!(targets.is_empty());
+    }
+}
@@ -0,0 +1,228 @@
+// The bevy 0.7 `Bundle` derive forgets the fields with `std::mem::forget`
+#![allow(clippy::forget_non_drop)]
+
+use bevy::{prelude::*, reflect::TypeUuid};
+use bevy_inspector_egui::Inspectable;
+use rand::Rng;
+use serde::Deserialize;
+
+use crate::{
+    common_component::{AtbGauge, CombatStats, EffectiveStats},
+    element::{Affinities, ElementAffinity},
+    encounter::{EncounterDatabase, EncounterDatabaseHandle, NextEncounter},
+    enemy_ai::AiProfile,
+    ron_loader::RonAssetLoader,
+    status_effect::StatusEffects,
+    AppState, GameData, SpriteSheet,
+};
+
+/// Max number of enemies a formation can have
+const MAX_ENEMIES: usize = 4;
+/// Horizontal distance between enemies in a formation
+const FORMATION_SPACING: f32 = 16.0;
+const FORMATION_CENTER: f32 = -16.0;
+
+// Plugin struct definitions
+#[derive(Debug, Component, Inspectable)]
+pub struct Enemy;
+
+/// The player can not flee from a combat with this enemy
+#[derive(Debug, Component, Inspectable)]
+pub struct BlocksEscape;
+
+/// What the player gets for defeating an enemy
+#[derive(Debug, Component, Inspectable)]
+pub struct Reward {
+    pub xp: u32,
+    pub gold: u32,
+    pub loot: Vec<LootDrop>,
+}
+
+#[derive(Bundle)]
+struct EnemyBundle {
+    name: Name,
+    tag: Enemy,
+    combat_stats: CombatStats,
+    effective_stats: EffectiveStats,
+    atb_gauge: AtbGauge,
+    status_effects: StatusEffects,
+    affinities: Affinities,
+    ai_profile: AiProfile,
+    reward: Reward,
+    #[bundle]
+    sprite: SpriteSheetBundle,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Inspectable)]
+pub struct LootDrop {
+    pub item: String,
+    /// Probability of the drop, from 0.0 to 1.0
+    pub chance: f32,
+}
+
+/// Definition of an enemy, as written in the enemy database
+#[derive(Debug, Clone, Deserialize)]
+pub struct EnemyData {
+    pub name: String,
+    pub sprite_index: usize,
+    pub max_hp: i32,
+    #[serde(default)]
+    pub max_mp: i32,
+    pub attack: i32,
+    pub defense: i32,
+    pub speed: i32,
+    pub accuracy: i32,
+    pub evasion: i32,
+    #[serde(default)]
+    pub blocks_escape: bool,
+    #[serde(default)]
+    pub affinities: Vec<ElementAffinity>,
+    #[serde(default)]
+    pub ai: AiProfile,
+    #[serde(default)]
+    pub xp: u32,
+    #[serde(default)]
+    pub gold: u32,
+    #[serde(default)]
+    pub loot: Vec<LootDrop>,
+}
+
+#[derive(Debug, Deserialize, TypeUuid)]
+#[uuid = "0d7acfb2-171c-44c8-8c2a-4213b4e9c588"]
+pub struct EnemyDatabase {
+    pub enemies: Vec<EnemyData>,
+}
+
+pub struct EnemyDatabaseHandle(Handle<EnemyDatabase>);
+
+pub struct EnemyPlugin;
+impl Plugin for EnemyPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_asset::<EnemyDatabase>()
+            .add_asset_loader(RonAssetLoader::<EnemyDatabase>::new(&["enemies.ron"]))
+            .add_asset::<EncounterDatabase>()
+            .add_asset_loader(
+                RonAssetLoader::<EncounterDatabase>::new(&["encounters.ron"])
+                    .with_validation(EncounterDatabase::validate),
+            )
+            .add_startup_system(load_enemy_database);
+
+        app.add_system_set(SystemSet::on_enter(AppState::Combat).with_system(spawn_enemy))
+            .add_system_set(SystemSet::on_update(AppState::Combat).with_system(hide_dead_enemies))
+            .add_system_set(SystemSet::on_exit(AppState::Combat).with_system(despawn_enemy));
+    }
+}
+
+fn load_enemy_database(
+    mut commands: Commands,
+    assets: Res<AssetServer>,
+    mut game_data: ResMut<GameData>,
+) {
+    let enemies = EnemyDatabaseHandle(assets.load("data/game.enemies.ron"));
+    let encounters = EncounterDatabaseHandle(assets.load("data/game.encounters.ron"));
+    game_data.0.push(enemies.0.clone_untyped());
+    game_data.0.push(encounters.0.clone_untyped());
+    commands.insert_resource(enemies);
+    commands.insert_resource(encounters);
+}
+
+/// Spawn the `NextEncounter`, or a random formation of any enemies without it
+fn spawn_enemy(
+    mut commands: Commands,
+    next_encounter: Option<Res<NextEncounter>>,
+    sprite_sheet: Res<SpriteSheet>,
+    database_handle: Res<EnemyDatabaseHandle>,
+    databases: Res<Assets<EnemyDatabase>>,
+) {
+    let database = databases
+        .get(&database_handle.0)
+        .expect("Enemy database not loaded 'EnemyPlugin (spawn_enemy)'");
+    if database.enemies.is_empty() {
+        warn!("No enemies on the enemy database");
+        return;
+    }
+
+    let mut rng = rand::thread_rng();
+    let mut can_flee = true;
+    let formation: Vec<_> = match next_encounter {
+        Some(next_encounter) => {
+            commands.remove_resource::<NextEncounter>();
+            can_flee = next_encounter.can_flee;
+            next_encounter
+                .enemies
+                .iter()
+                .filter_map(|name| {
+                    let enemy = database.enemies.iter().find(|enemy| &enemy.name == name);
+                    if enemy.is_none() {
+                        warn!("Unknown enemy {name} on the encounter");
+                    }
+                    enemy
+                })
+                .take(MAX_ENEMIES)
+                .collect()
+        }
+        None => (0..rng.gen_range(1..=MAX_ENEMIES))
+            .map(|_| &database.enemies[rng.gen_range(0..database.enemies.len())])
+            .collect(),
+    };
+    let formation_size = formation.len();
+    for (slot, enemy) in formation.into_iter().enumerate() {
+        let combat_stats = CombatStats {
+            hp: enemy.max_hp,
+            max_hp: enemy.max_hp,
+            mp: enemy.max_mp,
+            max_mp: enemy.max_mp,
+            attack: enemy.attack,
+            defense: enemy.defense,
+            speed: enemy.speed,
+            accuracy: enemy.accuracy,
+            evasion: enemy.evasion,
+        };
+        let mut enemy_commands = commands.spawn_bundle(EnemyBundle {
+            tag: Enemy,
+            name: Name::new(enemy.name.clone()),
+            effective_stats: EffectiveStats::from(&combat_stats),
+            combat_stats,
+            atb_gauge: AtbGauge::default(),
+            status_effects: StatusEffects::default(),
+            affinities: Affinities(enemy.affinities.clone()),
+            ai_profile: enemy.ai.clone(),
+            reward: Reward {
+                xp: enemy.xp,
+                gold: enemy.gold,
+                loot: enemy.loot.clone(),
+            },
+            sprite: SpriteSheetBundle {
+                sprite: TextureAtlasSprite::new(enemy.sprite_index),
+                texture_atlas: sprite_sheet.0.clone(),
+                transform: Transform::from_translation(formation_position(slot, formation_size)),
+                ..Default::default()
+            },
+        });
+        if enemy.blocks_escape || !can_flee {
+            enemy_commands.insert(BlocksEscape);
+        }
+    }
+}
+
+/// Position of the enemy in the `slot` of a formation of `size` enemies,
+/// the formation is centered on the left side of the combat screen, the party is on the right
+fn formation_position(slot: usize, size: usize) -> Vec3 {
+    let offset = slot as f32 - (size as f32 - 1.0) / 2.0;
+    Vec3::new(FORMATION_CENTER + offset * FORMATION_SPACING, 0.0, 10.0)
+}
+
+fn despawn_enemy(mut commands: Commands, enemy_query: Query<Entity, With<Enemy>>) {
+    for ent in enemy_query.iter() {
+        commands.entity(ent).despawn_recursive();
+    }
+}
+
+/// Dead enemies stay in the formation until the combat ends, they are only hidden
+fn hide_dead_enemies(mut enemy_query: Query<(&CombatStats, &mut Visibility), With<Enemy>>) {
+    for (combat_stats, mut visibility) in enemy_query.iter_mut() {
+        if combat_stats.hp <= 0 {
+            visibility.is_visible = false;
+        }
+    }
+}
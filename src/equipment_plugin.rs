@@ -0,0 +1,168 @@
+use bevy::{prelude::*, reflect::TypeUuid};
+use bevy_inspector_egui::Inspectable;
+use serde::Deserialize;
+
+use crate::{
+    common_component::{CombatStats, EffectiveStats},
+    inventory_plugin::Inventory,
+    ron_loader::RonAssetLoader,
+    GameData,
+};
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Deserialize)]
+pub enum EquipmentSlot {
+    Weapon,
+    Armor,
+    Accessory,
+}
+
+impl EquipmentSlot {
+    pub const ALL: [EquipmentSlot; 3] = [
+        EquipmentSlot::Weapon,
+        EquipmentSlot::Armor,
+        EquipmentSlot::Accessory,
+    ];
+}
+
+// Plugin struct definitions
+/// Names of the equipped items, as written in the equipment database
+#[derive(Debug, Clone, Default, Component, Deserialize, Inspectable)]
+#[serde(default)]
+pub struct Equipment {
+    pub weapon: Option<String>,
+    pub armor: Option<String>,
+    pub accessory: Option<String>,
+}
+
+impl Equipment {
+    pub fn get(&self, slot: EquipmentSlot) -> Option<&String> {
+        match slot {
+            EquipmentSlot::Weapon => self.weapon.as_ref(),
+            EquipmentSlot::Armor => self.armor.as_ref(),
+            EquipmentSlot::Accessory => self.accessory.as_ref(),
+        }
+    }
+
+    fn get_mut(&mut self, slot: EquipmentSlot) -> &mut Option<String> {
+        match slot {
+            EquipmentSlot::Weapon => &mut self.weapon,
+            EquipmentSlot::Armor => &mut self.armor,
+            EquipmentSlot::Accessory => &mut self.accessory,
+        }
+    }
+
+    /// Take `item` from the inventory and put it on the slot, the old one goes back to
+    /// the inventory. Without `item` the slot is emptied
+    pub fn equip(&mut self, slot: EquipmentSlot, item: Option<String>, inventory: &mut Inventory) {
+        if let Some(item) = &item {
+            if !inventory.remove_item(item) {
+                return;
+            }
+        }
+        if let Some(old) = std::mem::replace(self.get_mut(slot), item) {
+            inventory.add_item(&old, 1);
+        }
+    }
+}
+
+/// Definition of a piece of equipment, as written in the equipment database
+#[derive(Debug, Clone, Deserialize)]
+pub struct EquipmentData {
+    pub name: String,
+    pub slot: EquipmentSlot,
+    #[serde(default)]
+    pub attack: i32,
+    #[serde(default)]
+    pub defense: i32,
+}
+
+#[derive(Debug, Deserialize, TypeUuid)]
+#[uuid = "7f2d9c14-5a8e-4b36-9e1f-c04a6b8d3e57"]
+pub struct EquipmentDatabase {
+    pub equipment: Vec<EquipmentData>,
+}
+
+impl EquipmentDatabase {
+    pub fn get(&self, name: &str) -> Option<&EquipmentData> {
+        self.equipment
+            .iter()
+            .find(|equipment| equipment.name == name)
+    }
+
+    /// Base stats plus the modifiers of every equipped item
+    pub fn effective_stats(
+        &self,
+        combat_stats: &CombatStats,
+        equipment: Option<&Equipment>,
+    ) -> EffectiveStats {
+        let mut effective_stats = EffectiveStats::from(combat_stats);
+        let equipped = equipment
+            .into_iter()
+            .flat_map(|equipment| EquipmentSlot::ALL.map(|slot| equipment.get(slot)))
+            .flatten()
+            .filter_map(|name| self.get(name));
+        for item in equipped {
+            effective_stats.attack += item.attack;
+            effective_stats.defense += item.defense;
+        }
+        effective_stats
+    }
+}
+
+pub struct EquipmentDatabaseHandle(pub Handle<EquipmentDatabase>);
+
+pub struct EquipmentPlugin;
+impl Plugin for EquipmentPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_asset::<EquipmentDatabase>()
+            .add_asset_loader(RonAssetLoader::<EquipmentDatabase>::new(&["equipment.ron"]))
+            .add_startup_system(load_equipment_database)
+            .add_system(update_effective_stats);
+    }
+}
+
+fn load_equipment_database(
+    mut commands: Commands,
+    assets: Res<AssetServer>,
+    mut game_data: ResMut<GameData>,
+) {
+    let handle = EquipmentDatabaseHandle(assets.load("data/game.equipment.ron"));
+    game_data.0.push(handle.0.clone_untyped());
+    commands.insert_resource(handle);
+}
+
+/// Keep the `EffectiveStats` in sync when the base stats or the equipment change.
+/// Every combatant is updated when the database loads or changes on disk
+#[allow(clippy::type_complexity)]
+fn update_effective_stats(
+    mut database_event: EventReader<AssetEvent<EquipmentDatabase>>,
+    database_handle: Res<EquipmentDatabaseHandle>,
+    databases: Res<Assets<EquipmentDatabase>>,
+    mut stats_query: Query<(
+        &CombatStats,
+        Option<&Equipment>,
+        &mut EffectiveStats,
+        ChangeTrackers<CombatStats>,
+        Option<ChangeTrackers<Equipment>>,
+    )>,
+) {
+    let reloaded = database_event.iter().any(|event| match event {
+        AssetEvent::Created { handle } | AssetEvent::Modified { handle } => {
+            *handle == database_handle.0
+        }
+        _ => false,
+    });
+    let database = match databases.get(&database_handle.0) {
+        Some(database) => database,
+        None => return,
+    };
+    for (combat_stats, equipment, mut effective_stats, stats_tracker, equipment_tracker) in
+        stats_query.iter_mut()
+    {
+        let changed = stats_tracker.is_changed()
+            || equipment_tracker.is_some_and(|tracker| tracker.is_changed());
+        if reloaded || changed {
+            *effective_stats = database.effective_stats(combat_stats, equipment);
+        }
+    }
+}
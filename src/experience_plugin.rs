@@ -0,0 +1,125 @@
+use bevy::{prelude::*, reflect::TypeUuid};
+use bevy_inspector_egui::Inspectable;
+use serde::Deserialize;
+
+use crate::{common_component::CombatStats, ron_loader::RonAssetLoader, GameData};
+
+// Plugin struct definitions
+#[derive(Debug, Clone, Component, Inspectable)]
+pub struct Experience {
+    pub level: u32,
+    /// Total xp earned
+    pub xp: u32,
+}
+
+impl Default for Experience {
+    fn default() -> Self {
+        Self { level: 1, xp: 0 }
+    }
+}
+
+/// Total xp needed to reach a level and the stats gained with it
+#[derive(Debug, Clone, Deserialize)]
+pub struct LevelGrowth {
+    pub xp: u32,
+    pub max_hp: i32,
+    #[serde(default)]
+    pub max_mp: i32,
+    pub attack: i32,
+    pub defense: i32,
+    pub speed: i32,
+}
+
+#[derive(Debug, Deserialize, TypeUuid)]
+#[uuid = "f1d8d352-ff58-4af1-8f07-ebc1c083cb3e"]
+pub struct GrowthTable {
+    /// The first entry is level 2
+    pub levels: Vec<LevelGrowth>,
+}
+
+impl GrowthTable {
+    /// Growth to go from `level` to the next one, if there is one
+    fn next_level(&self, level: u32) -> Option<&LevelGrowth> {
+        self.levels.get(level as usize - 1)
+    }
+}
+
+pub struct GrowthTableHandle(Handle<GrowthTable>);
+
+pub struct ExperienceEvent {
+    pub target: Entity,
+    pub xp: u32,
+}
+
+pub struct LevelUpEvent {
+    pub target: Entity,
+    pub level: u32,
+}
+
+pub struct ExperiencePlugin;
+impl Plugin for ExperiencePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_asset::<GrowthTable>()
+            .add_asset_loader(RonAssetLoader::<GrowthTable>::new(&["growth.ron"]))
+            .add_event::<ExperienceEvent>()
+            .add_event::<LevelUpEvent>()
+            .add_startup_system(load_growth_table);
+
+        app.add_system(gain_experience)
+            .add_system(announce_level_up.after(gain_experience));
+    }
+}
+
+fn load_growth_table(
+    mut commands: Commands,
+    assets: Res<AssetServer>,
+    mut game_data: ResMut<GameData>,
+) {
+    let handle = GrowthTableHandle(assets.load("data/player.growth.ron"));
+    game_data.0.push(handle.0.clone_untyped());
+    commands.insert_resource(handle);
+}
+
+fn gain_experience(
+    mut experience_event: EventReader<ExperienceEvent>,
+    mut level_up_event: EventWriter<LevelUpEvent>,
+    table_handle: Res<GrowthTableHandle>,
+    tables: Res<Assets<GrowthTable>>,
+    mut experience_query: Query<(&mut Experience, &mut CombatStats)>,
+) {
+    for event in experience_event.iter() {
+        let table = tables
+            .get(&table_handle.0)
+            .expect("Growth table not loaded 'ExperiencePlugin (gain_experience)'");
+        let (mut experience, mut combat_stats) = match experience_query.get_mut(event.target) {
+            Ok(target) => target,
+            Err(_) => continue,
+        };
+        experience.xp += event.xp;
+        while let Some(growth) = table.next_level(experience.level) {
+            if experience.xp < growth.xp {
+                break;
+            }
+            experience.level += 1;
+            combat_stats.max_hp += growth.max_hp;
+            combat_stats.hp += growth.max_hp;
+            combat_stats.max_mp += growth.max_mp;
+            combat_stats.mp += growth.max_mp;
+            combat_stats.attack += growth.attack;
+            combat_stats.defense += growth.defense;
+            combat_stats.speed += growth.speed;
+            level_up_event.send(LevelUpEvent {
+                target: event.target,
+                level: experience.level,
+            });
+        }
+    }
+}
+
+fn announce_level_up(mut level_up_event: EventReader<LevelUpEvent>, name_query: Query<&Name>) {
+    for event in level_up_event.iter() {
+        if let Ok(name) = name_query.get(event.target) {
+            info!("{name} reached level {}", event.level);
+        }
+    }
+}
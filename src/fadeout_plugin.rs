@@ -1,3 +1,6 @@
+// The bevy 0.7 `Bundle` derive forgets the fields with `std::mem::forget`
+#![allow(clippy::forget_non_drop)]
+
 use bevy::prelude::*;
 
 use crate::{AppState, WIN_HEIGHT, WIN_WIDTH};
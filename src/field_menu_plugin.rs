@@ -0,0 +1,475 @@
+use bevy::prelude::*;
+
+use crate::{
+    common_component::{CombatStats, EffectiveStats},
+    equipment_plugin::{
+        Equipment, EquipmentData, EquipmentDatabase, EquipmentDatabaseHandle, EquipmentSlot,
+    },
+    inventory_plugin::{Inventory, ItemDatabase, ItemDatabaseHandle},
+    party_plugin::Party,
+    status_effect::StatusEffects,
+    AppState, Autosave, UiFont,
+};
+
+const COMMANDS: [&str; 2] = ["Items", "Equip"];
+const SELECTED_COLOR: Color = Color::rgb(1.0, 1.0, 1.0);
+const UNSELECTED_COLOR: Color = Color::rgb(0.5, 0.5, 0.5);
+/// Options that can not be chosen, like items that only work on combat
+const DISABLED_COLOR: Color = Color::rgb(0.25, 0.25, 0.25);
+
+// Plugin struct definitions
+#[derive(Debug, Component)]
+struct FieldMenuRoot;
+
+#[derive(Debug, Component)]
+struct FieldMenuText;
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+enum FieldMenuPage {
+    Commands,
+    Items,
+    /// Choosing the party member that uses the selected item
+    Target,
+    /// Choosing the party member to equip
+    Members,
+    /// Equipment slots of the chosen member
+    Slots,
+    /// Equipment of the inventory that fits the chosen slot
+    Gear,
+}
+
+/// What the menu shows of a party member
+struct MemberInfo<'a> {
+    name: &'a Name,
+    combat_stats: &'a CombatStats,
+    effective_stats: &'a EffectiveStats,
+    equipment: &'a Equipment,
+}
+
+/// Option shown on the menu
+struct MenuEntry {
+    label: String,
+    enabled: bool,
+}
+
+#[derive(Debug)]
+struct FieldMenu {
+    page: FieldMenuPage,
+    cursor: usize,
+    /// Item waiting for a target
+    item: Option<String>,
+    /// Index on the party of the member being equipped
+    member: usize,
+    slot: EquipmentSlot,
+}
+
+impl Default for FieldMenu {
+    fn default() -> Self {
+        Self {
+            page: FieldMenuPage::Commands,
+            cursor: 0,
+            item: None,
+            member: 0,
+            slot: EquipmentSlot::Weapon,
+        }
+    }
+}
+
+impl FieldMenu {
+    fn open(&mut self, page: FieldMenuPage) {
+        self.page = page;
+        self.cursor = 0;
+    }
+}
+
+/// Menu of the overworld, the `OverWorld` is paused while it is open
+pub struct FieldMenuPlugin;
+impl Plugin for FieldMenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<FieldMenu>();
+
+        app.add_system_set(SystemSet::on_update(AppState::OverWorld).with_system(open_field_menu));
+
+        app.add_system_set(SystemSet::on_enter(AppState::FieldMenu).with_system(spawn_field_menu))
+            .add_system_set(
+                SystemSet::on_update(AppState::FieldMenu)
+                    .with_system(field_menu_input)
+                    .with_system(update_field_menu_text.after(field_menu_input)),
+            )
+            .add_system_set(
+                SystemSet::on_exit(AppState::FieldMenu).with_system(despawn_field_menu),
+            );
+    }
+}
+
+fn open_field_menu(mut keyboard: ResMut<Input<KeyCode>>, mut state: ResMut<State<AppState>>) {
+    if keyboard.just_pressed(KeyCode::Tab) {
+        keyboard.reset(KeyCode::Tab);
+        state
+            .push(AppState::FieldMenu)
+            .expect("Error pushing state App::FieldMenu 'FieldMenu plugin'");
+    }
+}
+
+fn spawn_field_menu(
+    mut commands: Commands,
+    mut menu: ResMut<FieldMenu>,
+    mut autosave: ResMut<Autosave>,
+) {
+    *menu = FieldMenu::default();
+    autosave.0 = false;
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: Rect {
+                    left: Val::Px(16.0),
+                    top: Val::Px(16.0),
+                    ..Default::default()
+                },
+                padding: Rect::all(Val::Px(8.0)),
+                min_size: Size::new(Val::Px(240.0), Val::Auto),
+                ..Default::default()
+            },
+            color: UiColor(Color::rgba(0.0, 0.0, 0.3, 0.8)),
+            ..Default::default()
+        })
+        .insert(Name::new("Field menu"))
+        .insert(FieldMenuRoot)
+        .with_children(|parent| {
+            parent
+                .spawn_bundle(TextBundle {
+                    text: Text {
+                        sections: Vec::new(),
+                        alignment: TextAlignment::default(),
+                    },
+                    ..Default::default()
+                })
+                .insert(FieldMenuText);
+        });
+}
+
+fn despawn_field_menu(mut commands: Commands, menu_query: Query<Entity, With<FieldMenuRoot>>) {
+    for ent in menu_query.iter() {
+        commands.entity(ent).despawn_recursive();
+    }
+}
+
+/// Equipment of the inventory that fits on the slot, with how many are left
+fn slot_gear<'a>(
+    slot: EquipmentSlot,
+    inventory: &Inventory,
+    database: &'a EquipmentDatabase,
+) -> Vec<(&'a EquipmentData, u32)> {
+    inventory
+        .items
+        .iter()
+        .filter_map(|stack| database.get(&stack.item).map(|gear| (gear, stack.count)))
+        .filter(|(gear, _)| gear.slot == slot)
+        .collect()
+}
+
+/// Options shown on the current page of the menu, `members` are in party order
+fn menu_entries(
+    menu: &FieldMenu,
+    inventory: &Inventory,
+    item_database: &ItemDatabase,
+    equipment_database: &EquipmentDatabase,
+    members: &[MemberInfo],
+) -> Vec<MenuEntry> {
+    match menu.page {
+        FieldMenuPage::Commands => COMMANDS
+            .iter()
+            .map(|command| MenuEntry {
+                label: command.to_string(),
+                enabled: true,
+            })
+            .collect(),
+        FieldMenuPage::Items => inventory
+            .items
+            .iter()
+            .map(|stack| MenuEntry {
+                label: format!("{:<12}{:>3}", stack.item, stack.count),
+                enabled: item_database
+                    .get(&stack.item)
+                    .is_some_and(|item| item.usable_on_field()),
+            })
+            .collect(),
+        FieldMenuPage::Target => {
+            let effect = menu
+                .item
+                .as_ref()
+                .and_then(|item| item_database.get(item))
+                .map(|item| &item.effect);
+            members
+                .iter()
+                .map(|member| MenuEntry {
+                    label: format!(
+                        "{:<8}{:>3}/{:<3}{:>3}/{:<3}",
+                        member.name.as_str(),
+                        member.combat_stats.hp.max(0),
+                        member.combat_stats.max_hp,
+                        member.combat_stats.mp,
+                        member.combat_stats.max_mp
+                    ),
+                    enabled: effect.is_some_and(|effect| effect.can_target(member.combat_stats)),
+                })
+                .collect()
+        }
+        FieldMenuPage::Members => members
+            .iter()
+            .map(|member| MenuEntry {
+                label: format!(
+                    "{:<8}Atk{:>3} Def{:>3}",
+                    member.name.as_str(),
+                    member.effective_stats.attack,
+                    member.effective_stats.defense
+                ),
+                enabled: true,
+            })
+            .collect(),
+        FieldMenuPage::Slots => {
+            let equipment = match members.get(menu.member) {
+                Some(member) => member.equipment,
+                None => return Vec::new(),
+            };
+            EquipmentSlot::ALL
+                .iter()
+                .map(|slot| MenuEntry {
+                    label: format!(
+                        "{:<10}{}",
+                        format!("{slot:?}"),
+                        equipment.get(*slot).map_or("-", |name| name.as_str())
+                    ),
+                    enabled: true,
+                })
+                .collect()
+        }
+        FieldMenuPage::Gear => std::iter::once(MenuEntry {
+            label: String::from("Remove"),
+            enabled: true,
+        })
+        .chain(
+            slot_gear(menu.slot, inventory, equipment_database)
+                .into_iter()
+                .map(|(gear, count)| MenuEntry {
+                    label: format!(
+                        "{:<12}{:>3} Atk{:+} Def{:+}",
+                        gear.name, count, gear.attack, gear.defense
+                    ),
+                    enabled: true,
+                }),
+        )
+        .collect(),
+    }
+}
+
+/// Back goes to the previous page, closing the menu from the first one
+#[allow(clippy::too_many_arguments)]
+fn field_menu_input(
+    mut keyboard: ResMut<Input<KeyCode>>,
+    mut menu: ResMut<FieldMenu>,
+    mut state: ResMut<State<AppState>>,
+    mut inventory: ResMut<Inventory>,
+    party: Res<Party>,
+    item_handle: Res<ItemDatabaseHandle>,
+    item_databases: Res<Assets<ItemDatabase>>,
+    equipment_handle: Res<EquipmentDatabaseHandle>,
+    equipment_databases: Res<Assets<EquipmentDatabase>>,
+    mut member_query: Query<(
+        &Name,
+        &mut CombatStats,
+        &mut StatusEffects,
+        &mut Equipment,
+        &EffectiveStats,
+    )>,
+) {
+    let item_database = item_databases
+        .get(&item_handle.0)
+        .expect("Item database not loaded 'FieldMenuPlugin (field_menu_input)'");
+    let equipment_database = equipment_databases
+        .get(&equipment_handle.0)
+        .expect("Equipment database not loaded 'FieldMenuPlugin (field_menu_input)'");
+    let entries = {
+        let members: Vec<_> = party
+            .members
+            .iter()
+            .filter_map(|member| member_query.get(*member).ok())
+            .map(
+                |(name, combat_stats, _, equipment, effective_stats)| MemberInfo {
+                    name,
+                    combat_stats,
+                    effective_stats,
+                    equipment,
+                },
+            )
+            .collect();
+        menu_entries(
+            &menu,
+            &inventory,
+            item_database,
+            equipment_database,
+            &members,
+        )
+    };
+
+    if !entries.is_empty() && keyboard.just_pressed(KeyCode::Up) {
+        menu.cursor = (menu.cursor + entries.len() - 1) % entries.len();
+    }
+    if !entries.is_empty() && keyboard.just_pressed(KeyCode::Down) {
+        menu.cursor = (menu.cursor + 1) % entries.len();
+    }
+    let close = keyboard.just_pressed(KeyCode::Tab)
+        || (keyboard.just_pressed(KeyCode::Back) && menu.page == FieldMenuPage::Commands);
+    if close {
+        keyboard.reset(KeyCode::Tab);
+        keyboard.reset(KeyCode::Back);
+        state.pop().expect("Error poping FieldMenu state");
+        return;
+    }
+    if keyboard.just_pressed(KeyCode::Back) {
+        keyboard.reset(KeyCode::Back);
+        match menu.page {
+            FieldMenuPage::Target => {
+                menu.item = None;
+                menu.open(FieldMenuPage::Items);
+            }
+            FieldMenuPage::Slots => menu.open(FieldMenuPage::Members),
+            FieldMenuPage::Gear => menu.open(FieldMenuPage::Slots),
+            _ => menu.open(FieldMenuPage::Commands),
+        }
+        return;
+    }
+    if !keyboard.just_pressed(KeyCode::Space) && !keyboard.just_pressed(KeyCode::Return) {
+        return;
+    }
+    keyboard.reset(KeyCode::Space);
+    keyboard.reset(KeyCode::Return);
+
+    if !entries.get(menu.cursor).is_some_and(|entry| entry.enabled) {
+        return;
+    }
+    match menu.page {
+        FieldMenuPage::Commands => match menu.cursor {
+            0 => menu.open(FieldMenuPage::Items),
+            _ => menu.open(FieldMenuPage::Members),
+        },
+        FieldMenuPage::Items => {
+            menu.item = Some(inventory.items[menu.cursor].item.clone());
+            menu.open(FieldMenuPage::Target);
+        }
+        FieldMenuPage::Target => {
+            let item = match menu.item.as_ref().and_then(|item| item_database.get(item)) {
+                Some(item) => item,
+                None => return,
+            };
+            let (_, mut combat_stats, mut status_effects, _, _) = member_query
+                .get_mut(party.members[menu.cursor])
+                .expect("Can not get the party member 'FieldMenuPlugin (field_menu_input)'");
+            if !inventory.remove_item(&item.name) {
+                return;
+            }
+            item.effect.apply(&mut combat_stats, &mut status_effects);
+            // Keep using the same item while there are more
+            if !inventory.items.iter().any(|stack| stack.item == item.name) {
+                menu.item = None;
+                menu.open(FieldMenuPage::Items);
+            }
+        }
+        FieldMenuPage::Members => {
+            menu.member = menu.cursor;
+            menu.open(FieldMenuPage::Slots);
+        }
+        FieldMenuPage::Slots => {
+            menu.slot = EquipmentSlot::ALL[menu.cursor];
+            menu.open(FieldMenuPage::Gear);
+        }
+        FieldMenuPage::Gear => {
+            // The first option empties the slot
+            let gear = match menu.cursor {
+                0 => None,
+                cursor => slot_gear(menu.slot, &inventory, equipment_database)
+                    .get(cursor - 1)
+                    .map(|(gear, _)| gear.name.clone()),
+            };
+            let (_, _, _, mut equipment, _) = member_query
+                .get_mut(party.members[menu.member])
+                .expect("Can not get the party member 'FieldMenuPlugin (field_menu_input)'");
+            equipment.equip(menu.slot, gear, &mut inventory);
+            let slot = menu.slot;
+            menu.open(FieldMenuPage::Slots);
+            menu.cursor = EquipmentSlot::ALL
+                .iter()
+                .position(|s| *s == slot)
+                .unwrap_or_default();
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn update_field_menu_text(
+    menu: Res<FieldMenu>,
+    font: Res<UiFont>,
+    inventory: Res<Inventory>,
+    party: Res<Party>,
+    item_handle: Res<ItemDatabaseHandle>,
+    item_databases: Res<Assets<ItemDatabase>>,
+    equipment_handle: Res<EquipmentDatabaseHandle>,
+    equipment_databases: Res<Assets<EquipmentDatabase>>,
+    member_query: Query<(&Name, &CombatStats, &Equipment, &EffectiveStats)>,
+    mut text_query: Query<&mut Text, With<FieldMenuText>>,
+) {
+    let item_database = item_databases
+        .get(&item_handle.0)
+        .expect("Item database not loaded 'FieldMenuPlugin (update_field_menu_text)'");
+    let equipment_database = equipment_databases
+        .get(&equipment_handle.0)
+        .expect("Equipment database not loaded 'FieldMenuPlugin (update_field_menu_text)'");
+    let members: Vec<_> = party
+        .members
+        .iter()
+        .filter_map(|member| member_query.get(*member).ok())
+        .map(
+            |(name, combat_stats, equipment, effective_stats)| MemberInfo {
+                name,
+                combat_stats,
+                effective_stats,
+                equipment,
+            },
+        )
+        .collect();
+    let mut entries = menu_entries(
+        &menu,
+        &inventory,
+        item_database,
+        equipment_database,
+        &members,
+    );
+    if entries.is_empty() {
+        entries.push(MenuEntry {
+            label: String::from("Empty"),
+            enabled: false,
+        });
+    }
+    for mut text in text_query.iter_mut() {
+        text.sections = entries
+            .iter()
+            .enumerate()
+            .map(|(i, entry)| {
+                let selected = i == menu.cursor;
+                TextSection {
+                    value: format!("{}{}\n", if selected { "> " } else { "  " }, entry.label),
+                    style: TextStyle {
+                        font: font.0.clone(),
+                        font_size: 20.0,
+                        color: match (entry.enabled, selected) {
+                            (false, _) => DISABLED_COLOR,
+                            (true, true) => SELECTED_COLOR,
+                            (true, false) => UNSELECTED_COLOR,
+                        },
+                    },
+                }
+            })
+            .collect();
+    }
+}
@@ -0,0 +1,194 @@
+use bevy::{prelude::*, utils::HashMap};
+
+use crate::{AppState, UiFont};
+
+/// Time a number stays on screen
+const NUMBER_DURATION: f32 = 0.8;
+/// Distance the number rises before it disappears
+const NUMBER_RISE: f32 = 8.0;
+/// Distance between the number and the center of the target
+const NUMBER_OFFSET: f32 = 6.0;
+/// Space between the numbers of the hits a target takes at the same time
+const NUMBER_STACK: f32 = 5.0;
+/// The text is rendered bigger and scaled down to keep it sharp
+const NUMBER_FONT_SIZE: f32 = 24.0;
+const NUMBER_SCALE: f32 = 0.25;
+const FLASH_DURATION: f32 = 0.3;
+const FLASH_COLOR: Color = Color::rgb(1.0, 0.3, 0.3);
+/// Max horizontal distance of the shake
+const SHAKE_DISTANCE: f32 = 1.0;
+const SHAKE_SPEED: f32 = 60.0;
+
+const DAMAGE_COLOR: Color = Color::WHITE;
+const CRITICAL_COLOR: Color = Color::rgb(1.0, 0.8, 0.0);
+const HEAL_COLOR: Color = Color::rgb(0.3, 1.0, 0.3);
+const MP_COLOR: Color = Color::rgb(0.4, 0.6, 1.0);
+const MISS_COLOR: Color = Color::rgb(0.6, 0.6, 0.6);
+
+// Plugin struct definitions
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HitOutcome {
+    Damage { amount: i32, critical: bool },
+    Heal(i32),
+    RestoreMp(i32),
+    Miss,
+}
+
+/// Result of an action on a combatant, sent by the `CombatPlugin`
+pub struct HitEvent {
+    pub target: Entity,
+    pub outcome: HitOutcome,
+}
+
+/// Number that rises over the target and fades out
+#[derive(Debug, Component)]
+struct FloatingNumber {
+    timer: Timer,
+    start: Vec3,
+}
+
+/// The sprite was hit, it changes color and shakes for a moment
+#[derive(Debug, Component)]
+pub struct HitFlash {
+    timer: Timer,
+    /// Position of the sprite before the shake
+    origin: Vec3,
+}
+
+pub struct HitFeedbackPlugin;
+impl Plugin for HitFeedbackPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<HitEvent>();
+
+        app.add_system_set(
+            SystemSet::on_update(AppState::Combat)
+                .with_system(spawn_hit_feedback)
+                .with_system(float_numbers)
+                .with_system(flash_hits),
+        )
+        .add_system_set(SystemSet::on_exit(AppState::Combat).with_system(clear_hit_feedback));
+    }
+}
+
+fn spawn_hit_feedback(
+    mut commands: Commands,
+    font: Res<UiFont>,
+    mut hit_event: EventReader<HitEvent>,
+    target_query: Query<(&Transform, Option<&HitFlash>)>,
+) {
+    // Hits taken on the same frame stack instead of overlapping
+    let mut hits = HashMap::default();
+    for event in hit_event.iter() {
+        let (transform, flash) = match target_query.get(event.target) {
+            Ok(target) => target,
+            Err(_) => continue,
+        };
+        let (value, color) = match event.outcome {
+            HitOutcome::Damage {
+                amount,
+                critical: false,
+            } => (amount.to_string(), DAMAGE_COLOR),
+            HitOutcome::Damage {
+                amount,
+                critical: true,
+            } => (format!("{amount}!"), CRITICAL_COLOR),
+            HitOutcome::Heal(amount) => (amount.to_string(), HEAL_COLOR),
+            HitOutcome::RestoreMp(amount) => (format!("{amount}MP"), MP_COLOR),
+            HitOutcome::Miss => (String::from("Miss"), MISS_COLOR),
+        };
+        // The number starts over the resting position of the sprite, not the shaken one
+        let origin = flash.map_or(transform.translation, |flash| flash.origin);
+        let stack: &mut usize = hits.entry(event.target).or_default();
+        let y = NUMBER_OFFSET + *stack as f32 * NUMBER_STACK;
+        *stack += 1;
+        let start = origin + Vec3::new(0.0, y, 100.0);
+        commands
+            .spawn_bundle(Text2dBundle {
+                text: Text::with_section(
+                    value,
+                    TextStyle {
+                        font: font.0.clone(),
+                        font_size: NUMBER_FONT_SIZE,
+                        color,
+                    },
+                    TextAlignment {
+                        vertical: VerticalAlign::Center,
+                        horizontal: HorizontalAlign::Center,
+                    },
+                ),
+                transform: Transform::from_translation(start).with_scale(Vec3::splat(NUMBER_SCALE)),
+                ..Default::default()
+            })
+            .insert(Name::new("FloatingNumber"))
+            .insert(FloatingNumber {
+                timer: Timer::from_seconds(NUMBER_DURATION, false),
+                start,
+            });
+
+        if matches!(event.outcome, HitOutcome::Damage { amount, .. } if amount > 0) {
+            commands.entity(event.target).insert(HitFlash {
+                timer: Timer::from_seconds(FLASH_DURATION, false),
+                origin,
+            });
+        }
+    }
+}
+
+fn float_numbers(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut number_query: Query<(Entity, &mut FloatingNumber, &mut Transform, &mut Text)>,
+) {
+    for (entity, mut number, mut transform, mut text) in number_query.iter_mut() {
+        number.timer.tick(time.delta());
+        if number.timer.finished() {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+        let progress = number.timer.percent();
+        transform.translation = number.start + Vec3::new(0.0, progress * NUMBER_RISE, 0.0);
+        for section in text.sections.iter_mut() {
+            section.style.color.set_a(1.0 - progress);
+        }
+    }
+}
+
+fn flash_hits(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut flash_query: Query<(
+        Entity,
+        &mut HitFlash,
+        &mut Transform,
+        &mut TextureAtlasSprite,
+    )>,
+) {
+    for (entity, mut flash, mut transform, mut sprite) in flash_query.iter_mut() {
+        flash.timer.tick(time.delta());
+        if flash.timer.finished() {
+            transform.translation = flash.origin;
+            sprite.color = Color::WHITE;
+            commands.entity(entity).remove::<HitFlash>();
+            continue;
+        }
+        let elapsed = flash.timer.elapsed_secs();
+        transform.translation =
+            flash.origin + Vec3::new((elapsed * SHAKE_SPEED).sin() * SHAKE_DISTANCE, 0.0, 0.0);
+        sprite.color = FLASH_COLOR;
+    }
+}
+
+fn clear_hit_feedback(
+    mut commands: Commands,
+    number_query: Query<Entity, With<FloatingNumber>>,
+    mut flash_query: Query<(Entity, &HitFlash, &mut Transform, &mut TextureAtlasSprite)>,
+) {
+    for entity in number_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    for (entity, flash, mut transform, mut sprite) in flash_query.iter_mut() {
+        transform.translation = flash.origin;
+        sprite.color = Color::WHITE;
+        commands.entity(entity).remove::<HitFlash>();
+    }
+}
@@ -0,0 +1,199 @@
+use anyhow::anyhow;
+use bevy::{prelude::*, reflect::TypeUuid};
+use serde::Deserialize;
+
+use crate::{
+    ron_loader::RonAssetLoader,
+    skill_plugin::{SkillEffect, SkillTarget},
+    AppState, Autosave, GameData,
+};
+
+// Plugin struct definitions
+#[derive(Debug, Clone, Deserialize)]
+pub struct ItemStack {
+    pub item: String,
+    pub count: u32,
+}
+
+/// Gold and items shared by the whole party
+#[derive(Debug, Clone, Default)]
+pub struct Inventory {
+    pub gold: u32,
+    pub items: Vec<ItemStack>,
+}
+
+impl Inventory {
+    pub fn add_item(&mut self, item: &str, count: u32) {
+        if count == 0 {
+            return;
+        }
+        match self.items.iter_mut().find(|stack| stack.item == item) {
+            Some(stack) => stack.count += count,
+            None => self.items.push(ItemStack {
+                item: item.to_string(),
+                count,
+            }),
+        }
+    }
+
+    /// Take one item out of the inventory, returns false if there is none left
+    pub fn remove_item(&mut self, item: &str) -> bool {
+        let index = match self.items.iter().position(|stack| stack.item == item) {
+            Some(index) => index,
+            None => return false,
+        };
+        let count = match self.items[index].count.checked_sub(1) {
+            Some(count) => count,
+            None => {
+                self.items.remove(index);
+                return false;
+            }
+        };
+        self.items[index].count = count;
+        if count == 0 {
+            self.items.remove(index);
+        }
+        true
+    }
+}
+
+/// Definition of a consumable item, as written in the item database.
+/// Items use the same effects as the skills
+#[derive(Debug, Clone, Deserialize)]
+pub struct ItemData {
+    pub name: String,
+    pub effect: SkillEffect,
+    pub target: SkillTarget,
+}
+
+impl ItemData {
+    /// Outside of combat there are no enemies, the items are used on a party member
+    pub fn usable_on_field(&self) -> bool {
+        !matches!(self.effect, SkillEffect::Damage(_))
+            && matches!(self.target, SkillTarget::User | SkillTarget::Ally)
+    }
+}
+
+#[derive(Debug, Deserialize, TypeUuid)]
+#[uuid = "e3a1c6d8-47b2-4f59-8c0e-2d9b5a7f1e34"]
+pub struct ItemDatabase {
+    pub items: Vec<ItemData>,
+    /// Items the party has when a new game starts
+    pub starting_items: Vec<ItemStack>,
+}
+
+impl ItemDatabase {
+    pub fn get(&self, name: &str) -> Option<&ItemData> {
+        self.items.iter().find(|item| item.name == name)
+    }
+
+    /// The inventory has no empty stacks
+    pub fn validate(&self) -> Result<(), anyhow::Error> {
+        match self.starting_items.iter().find(|stack| stack.count == 0) {
+            Some(stack) => Err(anyhow!("Starting item {} without count", stack.item)),
+            None => Ok(()),
+        }
+    }
+}
+
+pub struct ItemDatabaseHandle(pub Handle<ItemDatabase>);
+
+/// Inventory on the last save
+#[derive(Debug)]
+struct InventorySave(Inventory);
+
+pub struct InventoryPlugin;
+impl Plugin for InventoryPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_asset::<ItemDatabase>()
+            .add_asset_loader(
+                RonAssetLoader::<ItemDatabase>::new(&["items.ron"])
+                    .with_validation(ItemDatabase::validate),
+            )
+            .init_resource::<Inventory>()
+            .add_startup_system(load_item_database);
+
+        app.add_system_set(
+            SystemSet::on_enter(AppState::OverWorld).with_system(fill_starting_inventory),
+        )
+        .add_system_set(SystemSet::on_resume(AppState::OverWorld).with_system(save_inventory))
+        .add_system_set(SystemSet::on_exit(AppState::GameOver).with_system(load_inventory));
+    }
+}
+
+fn load_item_database(
+    mut commands: Commands,
+    assets: Res<AssetServer>,
+    mut game_data: ResMut<GameData>,
+) {
+    let handle = ItemDatabaseHandle(assets.load("data/game.items.ron"));
+    game_data.0.push(handle.0.clone_untyped());
+    commands.insert_resource(handle);
+}
+
+fn fill_starting_inventory(
+    mut commands: Commands,
+    database_handle: Res<ItemDatabaseHandle>,
+    databases: Res<Assets<ItemDatabase>>,
+) {
+    let database = databases
+        .get(&database_handle.0)
+        .expect("Item database not loaded 'InventoryPlugin (fill_starting_inventory)'");
+    let inventory = Inventory {
+        gold: 0,
+        items: database.starting_items.clone(),
+    };
+    commands.insert_resource(InventorySave(inventory.clone()));
+    commands.insert_resource(inventory);
+}
+
+fn save_inventory(
+    autosave: Res<Autosave>,
+    mut inventory_save: ResMut<InventorySave>,
+    inventory: Res<Inventory>,
+) {
+    if !autosave.0 {
+        return;
+    }
+    inventory_save.0 = inventory.clone();
+}
+
+fn load_inventory(inventory_save: Res<InventorySave>, mut inventory: ResMut<Inventory>) {
+    *inventory = inventory_save.0.clone();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stack(item: &str, count: u32) -> ItemStack {
+        ItemStack {
+            item: String::from(item),
+            count,
+        }
+    }
+
+    #[test]
+    fn adding_nothing_makes_no_stack() {
+        let mut inventory = Inventory::default();
+        inventory.add_item("Potion", 0);
+        assert!(inventory.items.is_empty());
+        inventory.add_item("Potion", 2);
+        inventory.add_item("Potion", 1);
+        assert_eq!(inventory.items.len(), 1);
+        assert_eq!(inventory.items[0].count, 3);
+    }
+
+    #[test]
+    fn removing_the_last_item_removes_the_stack() {
+        let mut inventory = Inventory {
+            gold: 0,
+            items: vec![stack("Potion", 1), stack("Ether", 0)],
+        };
+        assert!(inventory.remove_item("Potion"));
+        assert!(!inventory.remove_item("Potion"));
+        // An empty stack has nothing to take
+        assert!(!inventory.remove_item("Ether"));
+        assert!(inventory.items.is_empty());
+    }
+}
@@ -1,9 +1,23 @@
+use battle_log_plugin::BattleLogPlugin;
+use battle_menu_plugin::BattleMenuPlugin;
 use bevy::{prelude::*, window::PresentMode};
 use camera_plugin::CameraPlugin;
+use combat_hud_plugin::CombatHudPlugin;
 use combat_plugin::CombatPlugin;
+use enemy_plugin::EnemyPlugin;
+use equipment_plugin::EquipmentPlugin;
+use experience_plugin::ExperiencePlugin;
 use fadeout_plugin::FadeoutPlugin;
+use field_menu_plugin::FieldMenuPlugin;
+use hit_feedback_plugin::HitFeedbackPlugin;
+use inventory_plugin::InventoryPlugin;
+use menu_plugin::MenuPlugin;
+use party_plugin::PartyPlugin;
 use player_plugin::PlayerPlugin;
-use tilemap_plugin::TilemapPlugin;
+use skill_plugin::SkillPlugin;
+use story_plugin::StoryPlugin;
+use tilemap_plugin::{MapPath, TilemapPlugin};
+use victory_plugin::VictoryPlugin;
 
 // Load and use this module on debug
 #[cfg(debug_assertions)]
@@ -11,14 +25,43 @@ use debug_plugin::DebugPlugin;
 #[cfg(debug_assertions)]
 mod debug_plugin;
 
+mod battle_log_plugin;
+mod battle_menu_plugin;
 mod camera_plugin;
+mod combat_hud_plugin;
 mod combat_plugin;
 mod common_component;
+mod damage;
+mod element;
+mod encounter;
+mod enemy_ai;
+mod enemy_plugin;
+mod equipment_plugin;
+mod experience_plugin;
 mod fadeout_plugin;
+mod field_menu_plugin;
+mod hit_feedback_plugin;
+mod inventory_plugin;
+mod menu_plugin;
+mod party_plugin;
 mod player_plugin;
+mod ron_loader;
+mod skill_plugin;
+mod status_effect;
+mod story_plugin;
+mod tiled_loader;
+mod tilemap_loader;
 mod tilemap_plugin;
+mod victory_plugin;
 
 struct SpriteSheet(Handle<TextureAtlas>);
+struct UiFont(Handle<Font>);
+/// The `OverWorld` saves the game when it resumes after a combat, not after the `FieldMenu`
+#[derive(Default)]
+struct Autosave(bool);
+/// Databases the game needs to start, the title screen waits until they are loaded
+#[derive(Default)]
+struct GameData(Vec<HandleUntyped>);
 
 const WIN_WIDTH: f32 = 200.0;
 const WIN_HEIGHT: f32 = 150.0;
@@ -27,10 +70,13 @@ const TILE_SIZE: f32 = 8.0;
 
 #[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
 pub enum AppState {
-    // MainMenu,
+    MainMenu,
     Fadeout,
     OverWorld,
     Combat,
+    GameOver,
+    /// Menu opened from the `OverWorld`
+    FieldMenu,
 }
 
 fn main() {
@@ -45,14 +91,42 @@ fn main() {
             present_mode: PresentMode::Fifo,
             ..Default::default()
         })
-        .insert_resource(ClearColor(Color::rgb(0.0, 0.0, 0.0)));
+        .insert_resource(ClearColor(Color::rgb(0.0, 0.0, 0.0)))
+        .init_resource::<Autosave>()
+        .init_resource::<GameData>();
+
+    // Another map can be given on the command line (ex: `cargo run -- maps/test.tmx`)
+    if let Some(map_path) = std::env::args().nth(1) {
+        app.insert_resource(MapPath(map_path));
+    }
+
+    // Reload the assets when they change on disk
+    #[cfg(debug_assertions)]
+    app.insert_resource(bevy::asset::AssetServerSettings {
+        watch_for_changes: true,
+        ..Default::default()
+    });
 
     app.add_plugins(DefaultPlugins)
         .add_plugin(PlayerPlugin)
+        .add_plugin(PartyPlugin)
         .add_plugin(TilemapPlugin)
+        .add_plugin(EnemyPlugin)
+        .add_plugin(SkillPlugin)
         .add_plugin(CombatPlugin)
+        .add_plugin(BattleMenuPlugin)
+        .add_plugin(BattleLogPlugin)
+        .add_plugin(CombatHudPlugin)
+        .add_plugin(HitFeedbackPlugin)
+        .add_plugin(VictoryPlugin)
+        .add_plugin(ExperiencePlugin)
+        .add_plugin(InventoryPlugin)
+        .add_plugin(EquipmentPlugin)
+        .add_plugin(FieldMenuPlugin)
+        .add_plugin(StoryPlugin)
         .add_plugin(CameraPlugin)
-        .add_plugin(FadeoutPlugin);
+        .add_plugin(FadeoutPlugin)
+        .add_plugin(MenuPlugin);
 
     // Add this plugins and system on debug
     #[cfg(debug_assertions)]
@@ -61,7 +135,7 @@ fn main() {
 
     app.add_startup_system_to_stage(StartupStage::PreStartup, load_assets);
 
-    app.add_state(AppState::OverWorld);
+    app.add_state(AppState::MainMenu);
 
     app.run();
 }
@@ -78,4 +152,5 @@ fn load_assets(
     let atlas_handle = texture_atlases.add(atlas);
 
     commands.insert_resource(SpriteSheet(atlas_handle));
+    commands.insert_resource(UiFont(assets.load("fonts/FiraMono-Medium.ttf")));
 }
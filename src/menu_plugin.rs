@@ -0,0 +1,198 @@
+use bevy::{asset::LoadState, prelude::*};
+
+use crate::{AppState, GameData, UiFont};
+
+const TITLE_OPTIONS: [&str; 1] = ["New Game"];
+const GAME_OVER_OPTIONS: [&str; 2] = ["Retry", "Title"];
+const SELECTED_COLOR: Color = Color::rgb(1.0, 1.0, 1.0);
+const UNSELECTED_COLOR: Color = Color::rgb(0.4, 0.4, 0.4);
+/// Options that can not be chosen yet, like a new game while the data loads
+const DISABLED_COLOR: Color = Color::rgb(0.15, 0.15, 0.15);
+
+// Plugin struct definitions
+/// Root of the menu UI, despawned when leaving the menu
+#[derive(Debug, Component)]
+struct MenuRoot;
+
+/// Option of the menu, the index is its position on the list
+#[derive(Debug, Component)]
+struct MenuOption(usize);
+
+/// Index of the selected option
+#[derive(Debug, Default)]
+struct MenuCursor(usize);
+
+pub struct MenuPlugin;
+impl Plugin for MenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MenuCursor>();
+
+        app.add_system_set(SystemSet::on_enter(AppState::MainMenu).with_system(spawn_title_screen))
+            .add_system_set(
+                SystemSet::on_update(AppState::MainMenu)
+                    .with_system(title_input)
+                    .with_system(highlight_option.after(title_input))
+                    .with_system(disable_new_game.after(highlight_option)),
+            )
+            .add_system_set(SystemSet::on_exit(AppState::MainMenu).with_system(despawn_menu));
+
+        app.add_system_set(
+            SystemSet::on_enter(AppState::GameOver).with_system(spawn_game_over_screen),
+        )
+        .add_system_set(
+            SystemSet::on_update(AppState::GameOver)
+                .with_system(game_over_input)
+                .with_system(highlight_option.after(game_over_input)),
+        )
+        .add_system_set(SystemSet::on_exit(AppState::GameOver).with_system(despawn_menu));
+    }
+}
+
+fn spawn_menu(commands: &mut Commands, font: &Handle<Font>, title: &str, options: &[&str]) {
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                flex_direction: FlexDirection::ColumnReverse,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..Default::default()
+            },
+            color: UiColor(Color::NONE),
+            ..Default::default()
+        })
+        .insert(Name::new("Menu"))
+        .insert(MenuRoot)
+        .with_children(|parent| {
+            parent.spawn_bundle(TextBundle {
+                style: Style {
+                    margin: Rect {
+                        bottom: Val::Px(32.0),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+                text: Text::with_section(
+                    title,
+                    TextStyle {
+                        font: font.clone(),
+                        font_size: 48.0,
+                        color: SELECTED_COLOR,
+                    },
+                    TextAlignment::default(),
+                ),
+                ..Default::default()
+            });
+            for (i, option) in options.iter().enumerate() {
+                parent
+                    .spawn_bundle(TextBundle {
+                        text: Text::with_section(
+                            *option,
+                            TextStyle {
+                                font: font.clone(),
+                                font_size: 24.0,
+                                color: UNSELECTED_COLOR,
+                            },
+                            TextAlignment::default(),
+                        ),
+                        ..Default::default()
+                    })
+                    .insert(MenuOption(i));
+            }
+        });
+}
+
+fn spawn_title_screen(mut commands: Commands, font: Res<UiFont>, mut cursor: ResMut<MenuCursor>) {
+    cursor.0 = 0;
+    spawn_menu(&mut commands, &font.0, "Final Rusty", &TITLE_OPTIONS);
+}
+
+fn spawn_game_over_screen(
+    mut commands: Commands,
+    font: Res<UiFont>,
+    mut cursor: ResMut<MenuCursor>,
+) {
+    cursor.0 = 0;
+    spawn_menu(&mut commands, &font.0, "Game Over", &GAME_OVER_OPTIONS);
+}
+
+fn despawn_menu(mut commands: Commands, menu_query: Query<Entity, With<MenuRoot>>) {
+    for ent in menu_query.iter() {
+        commands.entity(ent).despawn_recursive();
+    }
+}
+
+/// Move the cursor with the arrows, returns true when the option is confirmed
+fn move_cursor(keyboard: &mut Input<KeyCode>, cursor: &mut MenuCursor, options: usize) -> bool {
+    if keyboard.just_pressed(KeyCode::Up) {
+        cursor.0 = (cursor.0 + options - 1) % options;
+    }
+    if keyboard.just_pressed(KeyCode::Down) {
+        cursor.0 = (cursor.0 + 1) % options;
+    }
+    if keyboard.just_pressed(KeyCode::Return) {
+        keyboard.reset(KeyCode::Return);
+        return true;
+    }
+    false
+}
+
+fn game_data_loaded(assets: &AssetServer, game_data: &GameData) -> bool {
+    assets.get_group_load_state(game_data.0.iter().map(|handle| handle.id)) == LoadState::Loaded
+}
+
+/// A new game can only start once every database is loaded
+fn title_input(
+    mut keyboard: ResMut<Input<KeyCode>>,
+    mut cursor: ResMut<MenuCursor>,
+    mut state: ResMut<State<AppState>>,
+    assets: Res<AssetServer>,
+    game_data: Res<GameData>,
+) {
+    if move_cursor(&mut keyboard, &mut cursor, TITLE_OPTIONS.len())
+        && game_data_loaded(&assets, &game_data)
+    {
+        state
+            .set(AppState::OverWorld)
+            .expect("Error setting state to App::OverWorld 'Menu plugin'");
+    }
+}
+
+/// Retry resumes the `OverWorld`, the player loads the last save when leaving this state
+fn game_over_input(
+    mut keyboard: ResMut<Input<KeyCode>>,
+    mut cursor: ResMut<MenuCursor>,
+    mut state: ResMut<State<AppState>>,
+) {
+    if move_cursor(&mut keyboard, &mut cursor, GAME_OVER_OPTIONS.len()) {
+        match cursor.0 {
+            0 => state.pop().expect("Error poping GameOver state"),
+            _ => state
+                .replace(AppState::MainMenu)
+                .expect("Error replacing state with App::MainMenu 'Menu plugin'"),
+        }
+    }
+}
+
+fn disable_new_game(
+    assets: Res<AssetServer>,
+    game_data: Res<GameData>,
+    mut option_query: Query<(&MenuOption, &mut Text)>,
+) {
+    if game_data_loaded(&assets, &game_data) {
+        return;
+    }
+    for (_, mut text) in option_query.iter_mut().filter(|(option, _)| option.0 == 0) {
+        text.sections[0].style.color = DISABLED_COLOR;
+    }
+}
+
+fn highlight_option(cursor: Res<MenuCursor>, mut option_query: Query<(&MenuOption, &mut Text)>) {
+    for (option, mut text) in option_query.iter_mut() {
+        text.sections[0].style.color = if option.0 == cursor.0 {
+            SELECTED_COLOR
+        } else {
+            UNSELECTED_COLOR
+        };
+    }
+}
@@ -0,0 +1,425 @@
+// The bevy 0.7 `Bundle` derive forgets the fields with `std::mem::forget`
+#![allow(clippy::forget_non_drop)]
+
+use bevy::{prelude::*, reflect::TypeUuid};
+use bevy_inspector_egui::Inspectable;
+use serde::Deserialize;
+
+use crate::{
+    common_component::{AtbGauge, CombatStats, EffectiveStats},
+    element::Affinities,
+    equipment_plugin::Equipment,
+    experience_plugin::Experience,
+    hit_feedback_plugin::HitFlash,
+    player_plugin::Player,
+    ron_loader::RonAssetLoader,
+    skill_plugin::Skills,
+    status_effect::StatusEffects,
+    AppState, Autosave, GameData, SpriteSheet,
+};
+
+/// Max number of members in the party
+pub const MAX_PARTY_SIZE: usize = 4;
+/// Position of the first member on the combat screen, the rest line up below
+const LINEUP_X: f32 = 48.0;
+const LINEUP_TOP: f32 = 18.0;
+const LINEUP_SPACING: f32 = 12.0;
+const FALLEN_COLOR: Color = Color::rgb(0.3, 0.3, 0.3);
+
+// Plugin struct definitions
+#[derive(Debug, Component, Inspectable)]
+pub struct PartyMember;
+
+#[derive(Bundle)]
+struct PartyMemberBundle {
+    name: Name,
+    tag: PartyMember,
+    combat_stats: CombatStats,
+    effective_stats: EffectiveStats,
+    equipment: Equipment,
+    atb_gauge: AtbGauge,
+    status_effects: StatusEffects,
+    affinities: Affinities,
+    experience: Experience,
+    skills: Skills,
+    #[bundle]
+    sprite: SpriteSheetBundle,
+}
+
+/// Members of the party in order, the first one is the leader
+#[derive(Debug, Clone, Default)]
+pub struct Party {
+    pub members: Vec<Entity>,
+}
+
+impl Party {
+    pub fn leader(&self) -> Option<Entity> {
+        self.members.first().copied()
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.members.len() >= MAX_PARTY_SIZE
+    }
+}
+
+/// Definition of a playable character, as written in the party database
+#[derive(Debug, Clone, Deserialize)]
+pub struct CharacterData {
+    pub name: String,
+    pub sprite_index: usize,
+    pub max_hp: i32,
+    pub max_mp: i32,
+    pub attack: i32,
+    pub defense: i32,
+    pub speed: i32,
+    pub accuracy: i32,
+    pub evasion: i32,
+    #[serde(default)]
+    pub skills: Vec<String>,
+    #[serde(default)]
+    pub equipment: Equipment,
+}
+
+#[derive(Debug, Deserialize, TypeUuid)]
+#[uuid = "9c4e2a71-6d3b-4f0e-b8a5-1e7d3c9f4a62"]
+pub struct PartyDatabase {
+    pub characters: Vec<CharacterData>,
+    /// Names of the characters that start the game in the party
+    pub starting_party: Vec<String>,
+}
+
+pub struct PartyDatabaseHandle(Handle<PartyDatabase>);
+
+/// Change the party members, sent by the story
+pub enum PartyEvent {
+    /// The character joins at the end of the party, if there is room
+    Join(String),
+    Leave(String),
+}
+
+/// State of a member on the last save
+#[derive(Debug)]
+struct MemberSave {
+    entity: Entity,
+    /// Name on the `PartyDatabase`, to spawn the member again if it left the party
+    name: String,
+    combat_stats: CombatStats,
+    experience: Experience,
+    equipment: Equipment,
+}
+
+/// Party and members on the last save
+#[derive(Debug)]
+struct PartySave {
+    party: Party,
+    members: Vec<MemberSave>,
+}
+
+pub struct PartyPlugin;
+impl Plugin for PartyPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_asset::<PartyDatabase>()
+            .add_asset_loader(RonAssetLoader::<PartyDatabase>::new(&["party.ron"]))
+            .add_event::<PartyEvent>()
+            .init_resource::<Party>()
+            .add_startup_system(load_party_database);
+
+        app.add_system(change_party)
+            .add_system(show_leader.after(change_party));
+
+        app.add_system_set(SystemSet::on_enter(AppState::OverWorld).with_system(spawn_party))
+            .add_system_set(SystemSet::on_resume(AppState::OverWorld).with_system(save_party))
+            .add_system_set(SystemSet::on_exit(AppState::OverWorld).with_system(despawn_party))
+            .add_system_set(SystemSet::on_exit(AppState::GameOver).with_system(load_party));
+
+        app.add_system_set(SystemSet::on_enter(AppState::Combat).with_system(line_up_party))
+            .add_system_set(SystemSet::on_update(AppState::Combat).with_system(dim_fallen_members))
+            .add_system_set(SystemSet::on_exit(AppState::Combat).with_system(hide_party));
+    }
+}
+
+fn load_party_database(
+    mut commands: Commands,
+    assets: Res<AssetServer>,
+    mut game_data: ResMut<GameData>,
+) {
+    let handle = PartyDatabaseHandle(assets.load("data/game.party.ron"));
+    game_data.0.push(handle.0.clone_untyped());
+    commands.insert_resource(handle);
+}
+
+/// The `EffectiveStats` start as the base stats, the `EquipmentPlugin` adds the equipment
+fn member_bundle(character: &CharacterData, sprite_sheet: &SpriteSheet) -> PartyMemberBundle {
+    let combat_stats = CombatStats {
+        hp: character.max_hp,
+        max_hp: character.max_hp,
+        mp: character.max_mp,
+        max_mp: character.max_mp,
+        attack: character.attack,
+        defense: character.defense,
+        speed: character.speed,
+        accuracy: character.accuracy,
+        evasion: character.evasion,
+    };
+    PartyMemberBundle {
+        name: Name::new(character.name.clone()),
+        tag: PartyMember,
+        effective_stats: EffectiveStats::from(&combat_stats),
+        combat_stats,
+        equipment: character.equipment.clone(),
+        atb_gauge: AtbGauge::default(),
+        status_effects: StatusEffects::default(),
+        affinities: Affinities::default(),
+        experience: Experience::default(),
+        skills: Skills(character.skills.clone()),
+        sprite: SpriteSheetBundle {
+            sprite: TextureAtlasSprite::new(character.sprite_index),
+            texture_atlas: sprite_sheet.0.clone(),
+            // The members are only shown on combat
+            visibility: Visibility { is_visible: false },
+            ..Default::default()
+        },
+    }
+}
+
+fn spawn_party(
+    mut commands: Commands,
+    sprite_sheet: Res<SpriteSheet>,
+    database_handle: Res<PartyDatabaseHandle>,
+    databases: Res<Assets<PartyDatabase>>,
+    mut party: ResMut<Party>,
+) {
+    let database = databases
+        .get(&database_handle.0)
+        .expect("Party database not loaded 'PartyPlugin (spawn_party)'");
+
+    let mut members = Vec::new();
+    party.members.clear();
+    for name in database.starting_party.iter().take(MAX_PARTY_SIZE) {
+        let character = match database.characters.iter().find(|c| &c.name == name) {
+            Some(character) => character,
+            None => {
+                warn!("Unknown character {name} on the starting party");
+                continue;
+            }
+        };
+        let bundle = member_bundle(character, &sprite_sheet);
+        let combat_stats = bundle.combat_stats.clone();
+        let experience = bundle.experience.clone();
+        let equipment = bundle.equipment.clone();
+        let entity = commands.spawn_bundle(bundle).id();
+        party.members.push(entity);
+        members.push(MemberSave {
+            entity,
+            name: character.name.clone(),
+            combat_stats,
+            experience,
+            equipment,
+        });
+    }
+    commands.insert_resource(PartySave {
+        party: party.clone(),
+        members,
+    });
+}
+
+fn despawn_party(
+    mut commands: Commands,
+    mut party: ResMut<Party>,
+    member_query: Query<Entity, With<PartyMember>>,
+) {
+    party.members.clear();
+    for ent in member_query.iter() {
+        commands.entity(ent).despawn_recursive();
+    }
+}
+
+fn change_party(
+    mut commands: Commands,
+    mut party_event: EventReader<PartyEvent>,
+    mut party: ResMut<Party>,
+    sprite_sheet: Res<SpriteSheet>,
+    database_handle: Res<PartyDatabaseHandle>,
+    databases: Res<Assets<PartyDatabase>>,
+    name_query: Query<&Name, With<PartyMember>>,
+) {
+    for event in party_event.iter() {
+        match event {
+            PartyEvent::Join(name) => {
+                let character = databases
+                    .get(&database_handle.0)
+                    .and_then(|database| database.characters.iter().find(|c| &c.name == name));
+                match character {
+                    Some(character) if !party.is_full() => {
+                        let member = commands
+                            .spawn_bundle(member_bundle(character, &sprite_sheet))
+                            .id();
+                        party.members.push(member);
+                    }
+                    Some(_) => warn!("The party is full, {name} can not join"),
+                    None => warn!("Unknown character {name} can not join the party"),
+                }
+            }
+            PartyEvent::Leave(name) => {
+                let member = party
+                    .members
+                    .iter()
+                    .position(|member| name_query.get(*member).is_ok_and(|n| n.as_str() == name));
+                if let Some(index) = member {
+                    commands
+                        .entity(party.members.remove(index))
+                        .despawn_recursive();
+                }
+            }
+        }
+    }
+}
+
+/// The player on the overworld looks like the leader of the party
+fn show_leader(
+    party: Res<Party>,
+    member_query: Query<&TextureAtlasSprite, (With<PartyMember>, Without<Player>)>,
+    mut player_query: Query<&mut TextureAtlasSprite, With<Player>>,
+) {
+    let leader_sprite = match party
+        .leader()
+        .and_then(|leader| member_query.get(leader).ok())
+    {
+        Some(sprite) => sprite.index,
+        None => return,
+    };
+    for mut sprite in player_query.iter_mut() {
+        if sprite.index != leader_sprite {
+            sprite.index = leader_sprite;
+        }
+    }
+}
+
+fn save_party(
+    autosave: Res<Autosave>,
+    mut party_save: ResMut<PartySave>,
+    party: Res<Party>,
+    member_query: Query<(Entity, &Name, &CombatStats, &Experience, &Equipment), With<PartyMember>>,
+) {
+    if !autosave.0 {
+        return;
+    }
+    party_save.party = party.clone();
+    party_save.members = member_query
+        .iter()
+        .map(
+            |(entity, name, combat_stats, experience, equipment)| MemberSave {
+                entity,
+                name: name.to_string(),
+                combat_stats: combat_stats.clone(),
+                experience: experience.clone(),
+                equipment: equipment.clone(),
+            },
+        )
+        .collect();
+}
+
+/// Members that joined after the save leave the party again,
+/// members that left after the save join again
+fn load_party(
+    mut commands: Commands,
+    mut party_save: ResMut<PartySave>,
+    mut party: ResMut<Party>,
+    sprite_sheet: Res<SpriteSheet>,
+    database_handle: Res<PartyDatabaseHandle>,
+    databases: Res<Assets<PartyDatabase>>,
+    mut member_query: Query<
+        (Entity, &mut CombatStats, &mut Experience, &mut Equipment),
+        With<PartyMember>,
+    >,
+) {
+    let database = databases
+        .get(&database_handle.0)
+        .expect("Party database not loaded 'PartyPlugin (load_party)'");
+
+    for (entity, mut combat_stats, mut experience, mut equipment) in member_query.iter_mut() {
+        match party_save
+            .members
+            .iter()
+            .find(|saved| saved.entity == entity)
+        {
+            Some(saved) => {
+                *combat_stats = saved.combat_stats.clone();
+                *experience = saved.experience.clone();
+                *equipment = saved.equipment.clone();
+            }
+            None => commands.entity(entity).despawn_recursive(),
+        }
+    }
+
+    let PartySave {
+        party: saved_party,
+        members,
+    } = &mut *party_save;
+    for saved in members.iter_mut() {
+        if member_query.get(saved.entity).is_ok() {
+            continue;
+        }
+        let character = match database.characters.iter().find(|c| c.name == saved.name) {
+            Some(character) => character,
+            None => {
+                warn!("Unknown character {} on the save", saved.name);
+                continue;
+            }
+        };
+        let mut bundle = member_bundle(character, &sprite_sheet);
+        bundle.effective_stats = EffectiveStats::from(&saved.combat_stats);
+        bundle.combat_stats = saved.combat_stats.clone();
+        bundle.experience = saved.experience.clone();
+        bundle.equipment = saved.equipment.clone();
+        let entity = commands.spawn_bundle(bundle).id();
+        for member in saved_party.members.iter_mut() {
+            if *member == saved.entity {
+                *member = entity;
+            }
+        }
+        saved.entity = entity;
+    }
+    party.members = saved_party
+        .members
+        .iter()
+        .copied()
+        .filter(|member| members.iter().any(|saved| saved.entity == *member))
+        .collect();
+}
+
+fn line_up_party(
+    party: Res<Party>,
+    mut member_query: Query<(&mut Transform, &mut Visibility), With<PartyMember>>,
+) {
+    for (slot, member) in party.members.iter().enumerate() {
+        if let Ok((mut transform, mut visibility)) = member_query.get_mut(*member) {
+            let y = LINEUP_TOP - slot as f32 * LINEUP_SPACING;
+            transform.translation = Vec3::new(LINEUP_X, y, 10.0);
+            visibility.is_visible = true;
+        }
+    }
+}
+
+/// The `HitFeedbackPlugin` owns the color while the member flashes
+#[allow(clippy::type_complexity)]
+fn dim_fallen_members(
+    mut member_query: Query<
+        (&CombatStats, &mut TextureAtlasSprite),
+        (With<PartyMember>, Without<HitFlash>),
+    >,
+) {
+    for (combat_stats, mut sprite) in member_query.iter_mut() {
+        sprite.color = if combat_stats.hp > 0 {
+            Color::WHITE
+        } else {
+            FALLEN_COLOR
+        };
+    }
+}
+
+fn hide_party(mut member_query: Query<&mut Visibility, With<PartyMember>>) {
+    for mut visibility in member_query.iter_mut() {
+        visibility.is_visible = false;
+    }
+}
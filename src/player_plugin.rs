@@ -1,7 +1,14 @@
+// The bevy 0.7 `Bundle` derive forgets the fields with `std::mem::forget`
+#![allow(clippy::forget_non_drop)]
+
 use crate::{
-    common_component::{Collider, CombatStats, EncounterSpawn, Speed},
+    common_component::{Collider, EncounterSpawn, EncounterTrigger, Speed, Warp},
+    encounter::{EncounterDatabase, EncounterDatabaseHandle, NextEncounter},
     fadeout_plugin::FadeoutConfigResource,
-    AppState, SpriteSheet, TILE_SIZE,
+    story_plugin::{FixedEncounter, StoryFlags},
+    tilemap_loader::TileMap,
+    tilemap_plugin::{tile_translation, TileMapHandle},
+    AppState, Autosave, SpriteSheet, TILE_SIZE,
 };
 use bevy::{prelude::*, render::camera::Camera2d, sprite::collide_aabb::collide};
 use bevy_inspector_egui::Inspectable;
@@ -16,16 +23,25 @@ pub struct CombatTimer {
     remaining: f32,
     max_range: f32,
     min_range: f32,
+    /// Encounter region the player is walking, the range changes with it
+    region: Option<String>,
 }
 impl CombatTimer {
     fn new(min_range: f32, max_range: f32) -> Self {
         Self {
             max_range,
             min_range,
-            remaining: rand::thread_rng().gen_range(min_range..max_range),
+            remaining: rand::thread_rng().gen_range(min_range..=max_range),
+            region: None,
         }
     }
 
+    fn set_range(&mut self, min_range: f32, max_range: f32) {
+        self.min_range = min_range;
+        self.max_range = max_range;
+        self.reset();
+    }
+
     fn tick(&mut self, tick: f32) {
         self.remaining -= tick;
     }
@@ -35,17 +51,23 @@ impl CombatTimer {
     }
 
     fn reset(&mut self) {
-        self.remaining = rand::thread_rng().gen_range(self.min_range..self.max_range);
+        self.remaining = rand::thread_rng().gen_range(self.min_range..=self.max_range);
     }
 }
 
+/// Last safe state of the player, loaded to retry after a game over.
+/// The party and the inventory are saved by their own plugins
+#[derive(Debug)]
+pub struct SavePoint {
+    translation: Vec3,
+}
+
 #[derive(Bundle)]
 struct PlayerBundle {
     name: Name,
     tag: Player,
     speed: Speed,
     until_combat: CombatTimer,
-    combat_stats: CombatStats,
     #[bundle]
     sprite: SpriteSheetBundle,
 }
@@ -57,42 +79,89 @@ impl Plugin for PlayerPlugin {
             .add_system_set(
                 SystemSet::on_update(AppState::OverWorld)
                     .with_system(move_player)
-                    .with_system(camera_follow.after(move_player))
+                    .with_system(warp_player.after(move_player))
+                    .with_system(camera_follow.after(warp_player))
                     .with_system(check_encunter.after(move_player)),
             )
             // On combat enter
             .add_system_set(SystemSet::on_enter(AppState::Combat).with_system(hide_player))
-            // Always that the Overworld start show the player
-            .add_system_set(SystemSet::on_resume(AppState::OverWorld).with_system(show_player));
+            // Always that the Overworld start show the player, save the game after a combat
+            .add_system_set(
+                SystemSet::on_resume(AppState::OverWorld)
+                    .with_system(show_player)
+                    .with_system(save_game),
+            )
+            .add_system_set(SystemSet::on_exit(AppState::OverWorld).with_system(despawn_player))
+            // Leaving the game over screen always loads the last save
+            .add_system_set(SystemSet::on_exit(AppState::GameOver).with_system(load_save));
     }
 }
 
-fn spawn_player(mut commands: Commands, sprite_sheet: Res<SpriteSheet>) {
+/// The player only walks the overworld, it looks like the leader of the `Party`.
+/// It starts on the spawn of the map, or on the second tile of the second row without one
+fn spawn_player(
+    mut commands: Commands,
+    sprite_sheet: Res<SpriteSheet>,
+    map_handle: Res<TileMapHandle>,
+    maps: Res<Assets<TileMap>>,
+) {
+    let spawn = maps
+        .get(&map_handle.0)
+        .and_then(TileMap::spawn)
+        .unwrap_or(Vec2::ONE);
+    let translation = tile_translation(spawn, 10.0);
+    commands.insert_resource(SavePoint { translation });
     commands.spawn_bundle(PlayerBundle {
         tag: Player,
         name: Name::new("Player"),
         speed: Speed(32.0),
         until_combat: CombatTimer::new(20.0, 50.0),
-        combat_stats: CombatStats {
-            hp: 10,
-            max_hp: 10,
-            attack: 2,
-            defense: 1,
-        },
         sprite: SpriteSheetBundle {
             sprite: TextureAtlasSprite::new(8),
             texture_atlas: sprite_sheet.0.clone(),
-            transform: Transform::from_xyz(0.0, 0.0, 10.0),
+            transform: Transform::from_translation(translation),
             ..Default::default()
         },
     });
 }
 
+fn despawn_player(mut commands: Commands, player_query: Query<Entity, With<Player>>) {
+    for ent in player_query.iter() {
+        commands.entity(ent).despawn_recursive();
+    }
+}
+
+fn save_game(
+    autosave: Res<Autosave>,
+    mut save_point: ResMut<SavePoint>,
+    player_query: Query<&Transform, With<Player>>,
+) {
+    if !autosave.0 {
+        return;
+    }
+    let player_transform = player_query
+        .get_single()
+        .expect("No player found 'PlayerPlugin (save_game)'");
+    save_point.translation = player_transform.translation;
+}
+
+fn load_save(save_point: Res<SavePoint>, mut player_query: Query<&mut Transform, With<Player>>) {
+    let mut player_transform = player_query
+        .get_single_mut()
+        .expect("No player found 'PlayerPlugin (load_save)'");
+    player_transform.translation = save_point.translation;
+}
+
+/// Walking an encounter region brings the next encounter closer,
+/// entering a new region rolls the distance with the range of the region
+#[allow(clippy::too_many_arguments)]
 fn move_player(
     mut player_query: Query<(&mut Transform, &Speed), With<Player>>,
     mut encounter_query: Query<&mut CombatTimer, With<Player>>,
     collition_query: Query<&Transform, (With<Collider>, Without<Player>)>,
-    encounter_collition_query: Query<&Transform, (With<EncounterSpawn>, Without<Player>)>,
+    encounter_collition_query: Query<(&Transform, &EncounterSpawn), Without<Player>>,
+    encounter_handle: Res<EncounterDatabaseHandle>,
+    encounter_databases: Res<Assets<EncounterDatabase>>,
     keyboard: Res<Input<KeyCode>>,
     time: Res<Time>,
 ) {
@@ -134,18 +203,43 @@ fn move_player(
     {
         player_transform.translation += vel_y;
     }
-    if !encounter_collition_query.is_empty()
-        && encounter_collition_query
-            .iter()
-            .any(|w| check_colition(player_transform.translation, w.translation))
+    if let Some((_, spawn)) = encounter_collition_query
+        .iter()
+        .find(|(w, _)| check_colition(player_transform.translation, w.translation))
     {
         let mut encounter_timer = encounter_query
             .get_single_mut()
             .expect("No encounter timer found 'PlayerPlugin (move_player 127)'");
+        if encounter_timer.region.as_ref() != Some(&spawn.region) {
+            let database = encounter_databases
+                .get(&encounter_handle.0)
+                .expect("Encounter database not loaded 'PlayerPlugin (move_player)'");
+            match database.get(&spawn.region) {
+                Some(region) => encounter_timer.set_range(region.min_range, region.max_range),
+                None => warn!("Unknown encounter region {}", spawn.region),
+            }
+            encounter_timer.region = Some(spawn.region.clone());
+        }
         encounter_timer.tick(vel.x.abs() + vel.y.abs());
     }
 }
 
+fn warp_player(
+    mut player_query: Query<&mut Transform, With<Player>>,
+    warp_query: Query<(&Transform, &Warp), Without<Player>>,
+) {
+    let mut player_transform = player_query
+        .get_single_mut()
+        .expect("No player found 'PlayerPlugin (warp_player)'");
+    if let Some((_, warp)) = warp_query
+        .iter()
+        .find(|(w, _)| check_colition(player_transform.translation, w.translation))
+    {
+        player_transform.translation.x = warp.destination.x;
+        player_transform.translation.y = warp.destination.y;
+    }
+}
+
 fn check_colition(player_position: Vec3, collition_target: Vec3) -> bool {
     let collition = collide(
         player_position,
@@ -170,26 +264,68 @@ fn camera_follow(
     camera_transform.translation.y = player_transform.translation.y;
 }
 
-/// Look for trouble
+/// Look for trouble. The triggers on the map go first, they are ignored once their flag is set.
+/// Random enemies come from the table of the current region
+#[allow(clippy::too_many_arguments)]
 fn check_encunter(
     mut commands: Commands,
     mut encounter_query: Query<(&mut CombatTimer, &Transform), With<Player>>,
+    trigger_query: Query<(Entity, &Transform, &EncounterTrigger), Without<Player>>,
+    mut standing_on: Local<Vec<Entity>>,
     mut state: ResMut<State<AppState>>,
+    story_flags: Res<StoryFlags>,
+    encounter_handle: Res<EncounterDatabaseHandle>,
+    encounter_databases: Res<Assets<EncounterDatabase>>,
 ) {
     let (mut encounter_timer, player_transform) = encounter_query
         .get_single_mut()
         .expect("No encounter timer found 'Player plugin' 163");
-    if encounter_timer.is_done() {
-        encounter_timer.reset();
-        commands.insert_resource(FadeoutConfigResource {
-            fadeout_duration: 0.75,
-            next_state: Some(AppState::Combat),
-            position: player_transform.translation,
+    let database = encounter_databases
+        .get(&encounter_handle.0)
+        .expect("Encounter database not loaded 'PlayerPlugin (check_encunter)'");
+    // A trigger only fires when the player steps on it, staying on it after fleeing does nothing
+    let triggers: Vec<_> = trigger_query
+        .iter()
+        .filter(|(_, w, _)| check_colition(player_transform.translation, w.translation))
+        .collect();
+    let fixed_encounter = triggers
+        .iter()
+        .filter(|(entity, _, _)| !standing_on.contains(entity))
+        .filter_map(|(_, _, trigger)| database.get_fixed(&trigger.encounter))
+        .find(|encounter| !story_flags.is_set(&encounter.flag));
+    *standing_on = triggers.iter().map(|(entity, _, _)| *entity).collect();
+
+    if let Some(fixed_encounter) = fixed_encounter {
+        commands.insert_resource(NextEncounter {
+            enemies: fixed_encounter.enemies.clone(),
+            can_flee: fixed_encounter.can_flee,
         });
-        state
-            .push(AppState::Fadeout)
-            .expect("Error pushing state to App::Fadeout 'Player plugin' 184");
+        commands.insert_resource(FixedEncounter(fixed_encounter.clone()));
+    } else if encounter_timer.is_done() {
+        encounter_timer.reset();
+        let formation = encounter_timer
+            .region
+            .as_ref()
+            .and_then(|region| database.get(region))
+            .and_then(|region| region.choose_formation(&mut rand::thread_rng()));
+        match formation {
+            Some(formation) => commands.insert_resource(NextEncounter {
+                enemies: formation.enemies.clone(),
+                can_flee: true,
+            }),
+            None => commands.remove_resource::<NextEncounter>(),
+        }
+    } else {
+        return;
     }
+    commands.insert_resource(FadeoutConfigResource {
+        fadeout_duration: 0.75,
+        next_state: Some(AppState::Combat),
+        position: player_transform.translation,
+    });
+    state
+        .push(AppState::Fadeout)
+        .expect("Error pushing state to App::Fadeout 'Player plugin' 184");
 }
 
 fn hide_player(
@@ -0,0 +1,50 @@
+use std::marker::PhantomData;
+
+use bevy::asset::{Asset, AssetLoader, BoxedFuture, LoadContext, LoadedAsset};
+use serde::Deserialize;
+
+/// Generic loader for data assets written in RON,
+/// each asset type use its own extension (ex: `game.enemies.ron`)
+pub struct RonAssetLoader<T> {
+    extensions: &'static [&'static str],
+    /// Checks the asset once parsed, an error fails the load
+    validate: fn(&T) -> Result<(), anyhow::Error>,
+    marker: PhantomData<T>,
+}
+
+impl<T> RonAssetLoader<T> {
+    pub fn new(extensions: &'static [&'static str]) -> Self {
+        Self {
+            extensions,
+            validate: |_| Ok(()),
+            marker: PhantomData,
+        }
+    }
+
+    pub fn with_validation(mut self, validate: fn(&T) -> Result<(), anyhow::Error>) -> Self {
+        self.validate = validate;
+        self
+    }
+}
+
+impl<T> AssetLoader for RonAssetLoader<T>
+where
+    T: Asset + for<'de> Deserialize<'de>,
+{
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), anyhow::Error>> {
+        Box::pin(async move {
+            let asset = ron::de::from_bytes::<T>(bytes)?;
+            (self.validate)(&asset)?;
+            load_context.set_default_asset(LoadedAsset::new(asset));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        self.extensions
+    }
+}
@@ -0,0 +1,111 @@
+use bevy::{prelude::*, reflect::TypeUuid};
+use bevy_inspector_egui::Inspectable;
+use serde::Deserialize;
+
+use crate::{
+    common_component::CombatStats,
+    element::Element,
+    ron_loader::RonAssetLoader,
+    status_effect::{StatusEffect, StatusEffects, StatusKind},
+    GameData,
+};
+
+// Plugin struct definitions
+/// Names of the skills a combatant can use
+#[derive(Debug, Clone, Default, Component, Inspectable)]
+pub struct Skills(pub Vec<String>);
+
+#[derive(Debug, Clone, Deserialize)]
+pub enum SkillEffect {
+    /// Damage that adds the attack of the user
+    Damage(i32),
+    /// Restore hp up to the max hp
+    Heal(i32),
+    /// Restore mp up to the max mp
+    RestoreMp(i32),
+    /// Add a status effect to the target
+    Inflict(StatusEffect),
+    /// Remove a status effect from the target
+    RemoveStatus(StatusKind),
+    /// Bring back a fallen target with this hp
+    Revive(i32),
+}
+
+impl SkillEffect {
+    /// Only the revives can target the fallen, and only them
+    pub fn can_target(&self, combat_stats: &CombatStats) -> bool {
+        (combat_stats.hp > 0) != matches!(self, SkillEffect::Revive(_))
+    }
+
+    /// Apply the effects that don't depend on the user, the `Damage` is rolled by the combat
+    pub fn apply(&self, combat_stats: &mut CombatStats, status_effects: &mut StatusEffects) {
+        match self {
+            SkillEffect::Damage(_) => {}
+            SkillEffect::Heal(power) => {
+                combat_stats.hp = i32::min(combat_stats.hp + power, combat_stats.max_hp);
+            }
+            SkillEffect::RestoreMp(power) => {
+                combat_stats.mp = i32::min(combat_stats.mp + power, combat_stats.max_mp);
+            }
+            SkillEffect::Inflict(status_effect) => status_effects.add(status_effect.clone()),
+            SkillEffect::RemoveStatus(kind) => status_effects.remove(*kind),
+            SkillEffect::Revive(power) => {
+                combat_stats.hp = i32::min(*power, combat_stats.max_hp);
+                status_effects.clear();
+            }
+        }
+    }
+}
+
+/// Who can be targeted, enemies and allies are relative to the user of the skill
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Deserialize)]
+pub enum SkillTarget {
+    Enemy,
+    AllEnemies,
+    User,
+    Ally,
+}
+
+/// Definition of a skill, as written in the skill database
+#[derive(Debug, Clone, Deserialize)]
+pub struct SkillData {
+    pub name: String,
+    pub mp_cost: i32,
+    #[serde(default)]
+    pub element: Element,
+    pub effect: SkillEffect,
+    pub target: SkillTarget,
+}
+
+#[derive(Debug, Deserialize, TypeUuid)]
+#[uuid = "5b0b6a8e-2f4c-4d8e-9a51-3c7e1f6d2b94"]
+pub struct SkillDatabase {
+    pub skills: Vec<SkillData>,
+}
+
+impl SkillDatabase {
+    pub fn get(&self, name: &str) -> Option<&SkillData> {
+        self.skills.iter().find(|skill| skill.name == name)
+    }
+}
+
+pub struct SkillDatabaseHandle(pub Handle<SkillDatabase>);
+
+pub struct SkillPlugin;
+impl Plugin for SkillPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_asset::<SkillDatabase>()
+            .add_asset_loader(RonAssetLoader::<SkillDatabase>::new(&["skills.ron"]))
+            .add_startup_system(load_skill_database);
+    }
+}
+
+fn load_skill_database(
+    mut commands: Commands,
+    assets: Res<AssetServer>,
+    mut game_data: ResMut<GameData>,
+) {
+    let handle = SkillDatabaseHandle(assets.load("data/game.skills.ron"));
+    game_data.0.push(handle.0.clone_untyped());
+    commands.insert_resource(handle);
+}
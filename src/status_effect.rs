@@ -0,0 +1,181 @@
+use bevy::prelude::Component;
+use bevy_inspector_egui::Inspectable;
+use serde::Deserialize;
+
+use crate::common_component::CombatStats;
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Deserialize, Inspectable)]
+pub enum StatusKind {
+    /// Lose `potency` hp at the start of each turn
+    Poison,
+    /// Can not act, taking damage wakes up the combatant
+    Sleep,
+    /// Can not act
+    Stun,
+    AttackUp,
+    AttackDown,
+    DefenseUp,
+    DefenseDown,
+}
+
+#[derive(Debug, Clone, Deserialize, Inspectable)]
+pub struct StatusEffect {
+    pub kind: StatusKind,
+    /// Remaining turns, it goes down at the start of each turn of the owner
+    pub turns: u32,
+    /// Hp lost by poison or stat points added or removed
+    #[serde(default)]
+    pub potency: i32,
+}
+
+impl Default for StatusEffect {
+    fn default() -> Self {
+        Self {
+            kind: StatusKind::Poison,
+            turns: 0,
+            potency: 0,
+        }
+    }
+}
+
+/// Status effects of a combatant, cleared when the combat ends
+#[derive(Debug, Clone, Default, Component, Inspectable)]
+pub struct StatusEffects(pub Vec<StatusEffect>);
+
+impl StatusEffects {
+    /// Add an effect, an effect of the same kind is replaced
+    pub fn add(&mut self, effect: StatusEffect) {
+        self.remove(effect.kind);
+        self.0.push(effect);
+    }
+
+    pub fn remove(&mut self, kind: StatusKind) {
+        self.0.retain(|effect| effect.kind != kind);
+    }
+
+    pub fn clear(&mut self) {
+        self.0.clear();
+    }
+
+    /// Apply the effects at the start of the owner turn,
+    /// returns false when the owner can not act on this turn
+    pub fn start_turn(&mut self, combat_stats: &mut CombatStats) -> bool {
+        let mut can_act = true;
+        for effect in self.0.iter_mut() {
+            match effect.kind {
+                StatusKind::Poison => combat_stats.hp -= effect.potency,
+                StatusKind::Sleep | StatusKind::Stun => can_act = false,
+                _ => {}
+            }
+            effect.turns = effect.turns.saturating_sub(1);
+        }
+        self.0.retain(|effect| effect.turns > 0);
+        can_act && combat_stats.hp > 0
+    }
+
+    /// Attack added by the effects, can be negative
+    pub fn attack(&self) -> i32 {
+        self.modifier(StatusKind::AttackUp, StatusKind::AttackDown)
+    }
+
+    /// Defense added by the effects, can be negative
+    pub fn defense(&self) -> i32 {
+        self.modifier(StatusKind::DefenseUp, StatusKind::DefenseDown)
+    }
+
+    fn modifier(&self, up: StatusKind, down: StatusKind) -> i32 {
+        self.0
+            .iter()
+            .map(|effect| match effect.kind {
+                kind if kind == up => effect.potency,
+                kind if kind == down => -effect.potency,
+                _ => 0,
+            })
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stats(hp: i32) -> CombatStats {
+        CombatStats {
+            hp,
+            max_hp: 100,
+            mp: 0,
+            max_mp: 0,
+            attack: 10,
+            defense: 5,
+            speed: 5,
+            accuracy: 90,
+            evasion: 5,
+        }
+    }
+
+    fn effect(kind: StatusKind, turns: u32, potency: i32) -> StatusEffect {
+        StatusEffect {
+            kind,
+            turns,
+            potency,
+        }
+    }
+
+    fn turns(effects: &StatusEffects) -> Vec<(StatusKind, u32)> {
+        effects
+            .0
+            .iter()
+            .map(|effect| (effect.kind, effect.turns))
+            .collect()
+    }
+
+    #[test]
+    fn effects_tick_down_and_expire() {
+        let mut effects = StatusEffects(vec![
+            effect(StatusKind::AttackUp, 2, 5),
+            effect(StatusKind::DefenseDown, 1, 3),
+        ]);
+        let mut combat_stats = stats(50);
+
+        assert!(effects.start_turn(&mut combat_stats));
+        assert_eq!(turns(&effects), vec![(StatusKind::AttackUp, 1)]);
+        assert_eq!(effects.defense(), 0);
+
+        assert!(effects.start_turn(&mut combat_stats));
+        assert!(effects.0.is_empty());
+        assert_eq!(effects.attack(), 0);
+        assert_eq!(combat_stats.hp, 50);
+    }
+
+    #[test]
+    fn poison_damages_each_turn() {
+        let mut effects = StatusEffects(vec![effect(StatusKind::Poison, 2, 7)]);
+        let mut combat_stats = stats(50);
+
+        assert!(effects.start_turn(&mut combat_stats));
+        assert_eq!(combat_stats.hp, 43);
+        assert!(effects.start_turn(&mut combat_stats));
+        assert_eq!(combat_stats.hp, 36);
+        assert!(effects.0.is_empty());
+        assert!(effects.start_turn(&mut combat_stats));
+        assert_eq!(combat_stats.hp, 36);
+    }
+
+    #[test]
+    fn poison_that_kills_skips_the_turn() {
+        let mut effects = StatusEffects(vec![effect(StatusKind::Poison, 3, 10)]);
+        let mut combat_stats = stats(5);
+        assert!(!effects.start_turn(&mut combat_stats));
+    }
+
+    #[test]
+    fn sleep_skips_the_turn_until_it_expires() {
+        let mut effects = StatusEffects(vec![effect(StatusKind::Sleep, 2, 0)]);
+        let mut combat_stats = stats(50);
+
+        assert!(!effects.start_turn(&mut combat_stats));
+        assert!(!effects.start_turn(&mut combat_stats));
+        assert!(effects.start_turn(&mut combat_stats));
+        assert_eq!(combat_stats.hp, 50);
+    }
+}
@@ -0,0 +1,137 @@
+use bevy::{audio::AudioSink, prelude::*, utils::HashSet};
+
+use crate::{
+    combat_plugin::CombatState, encounter::FixedEncounterData, party_plugin::PartyEvent, AppState,
+    Autosave, WIN_HEIGHT, WIN_WIDTH,
+};
+
+// Plugin struct definitions
+/// Events of the story that already happened, like a defeated boss
+#[derive(Debug, Clone, Default)]
+pub struct StoryFlags(HashSet<String>);
+
+impl StoryFlags {
+    pub fn set(&mut self, flag: &str) {
+        self.0.insert(flag.to_string());
+    }
+
+    pub fn is_set(&self, flag: &str) -> bool {
+        self.0.contains(flag)
+    }
+}
+
+/// Story flags on the last save
+#[derive(Debug)]
+struct StoryFlagsSave(StoryFlags);
+
+/// Fixed encounter of the current combat, inserted by the trigger that started it
+#[derive(Debug)]
+pub struct FixedEncounter(pub FixedEncounterData);
+
+#[derive(Debug, Component)]
+struct CombatBackground;
+
+/// Music of the current combat, kept to stop it when the combat ends
+struct CombatMusic(Handle<AudioSink>);
+
+pub struct StoryPlugin;
+impl Plugin for StoryPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<StoryFlags>();
+
+        app.add_system_set(SystemSet::on_enter(AppState::OverWorld).with_system(reset_story))
+            .add_system_set(SystemSet::on_resume(AppState::OverWorld).with_system(save_story))
+            .add_system_set(SystemSet::on_exit(AppState::GameOver).with_system(load_story));
+
+        app.add_system_set(
+            SystemSet::on_enter(AppState::Combat).with_system(start_fixed_encounter),
+        )
+        .add_system_set(SystemSet::on_enter(CombatState::Victory).with_system(set_victory_flag))
+        .add_system_set(SystemSet::on_exit(AppState::Combat).with_system(end_fixed_encounter));
+    }
+}
+
+/// A new game starts without flags
+fn reset_story(mut commands: Commands, mut story_flags: ResMut<StoryFlags>) {
+    *story_flags = StoryFlags::default();
+    commands.insert_resource(StoryFlagsSave(StoryFlags::default()));
+}
+
+fn save_story(
+    autosave: Res<Autosave>,
+    mut story_save: ResMut<StoryFlagsSave>,
+    story_flags: Res<StoryFlags>,
+) {
+    if !autosave.0 {
+        return;
+    }
+    story_save.0 = story_flags.clone();
+}
+
+fn load_story(story_save: Res<StoryFlagsSave>, mut story_flags: ResMut<StoryFlags>) {
+    *story_flags = story_save.0.clone();
+}
+
+fn start_fixed_encounter(
+    mut commands: Commands,
+    fixed_encounter: Option<Res<FixedEncounter>>,
+    assets: Res<AssetServer>,
+    audio: Res<Audio>,
+    audio_sinks: Res<Assets<AudioSink>>,
+) {
+    let encounter = match fixed_encounter {
+        Some(fixed_encounter) => fixed_encounter.0.clone(),
+        None => return,
+    };
+    if let Some((r, g, b)) = encounter.background {
+        commands
+            .spawn_bundle(SpriteBundle {
+                sprite: Sprite {
+                    color: Color::rgb(r, g, b),
+                    custom_size: Some(Vec2::new(WIN_WIDTH, WIN_HEIGHT)),
+                    ..Default::default()
+                },
+                transform: Transform::from_xyz(0.0, 0.0, 1.0),
+                ..Default::default()
+            })
+            .insert(Name::new("Combat background"))
+            .insert(CombatBackground);
+    }
+    if let Some(music) = encounter.music {
+        let sink = audio.play_with_settings(assets.load(&music), PlaybackSettings::LOOP);
+        commands.insert_resource(CombatMusic(audio_sinks.get_handle(sink)));
+    }
+}
+
+/// Winning a fixed encounter sets its flag and changes the party members
+fn set_victory_flag(
+    fixed_encounter: Option<Res<FixedEncounter>>,
+    mut story_flags: ResMut<StoryFlags>,
+    mut party_event: EventWriter<PartyEvent>,
+) {
+    if let Some(fixed_encounter) = fixed_encounter {
+        story_flags.set(&fixed_encounter.0.flag);
+        for name in fixed_encounter.0.leaves.iter() {
+            party_event.send(PartyEvent::Leave(name.clone()));
+        }
+        for name in fixed_encounter.0.joins.iter() {
+            party_event.send(PartyEvent::Join(name.clone()));
+        }
+    }
+}
+
+fn end_fixed_encounter(
+    mut commands: Commands,
+    combat_music: Option<Res<CombatMusic>>,
+    audio_sinks: Res<Assets<AudioSink>>,
+    background_query: Query<Entity, With<CombatBackground>>,
+) {
+    for ent in background_query.iter() {
+        commands.entity(ent).despawn_recursive();
+    }
+    if let Some(sink) = combat_music.and_then(|music| audio_sinks.get(&music.0)) {
+        sink.stop();
+    }
+    commands.remove_resource::<CombatMusic>();
+    commands.remove_resource::<FixedEncounter>();
+}
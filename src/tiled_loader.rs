@@ -0,0 +1,661 @@
+use std::{collections::HashMap, fmt::Display, path::Path, str::FromStr};
+
+use anyhow::{anyhow, bail};
+use bevy::{
+    asset::{AssetLoader, BoxedFuture, LoadContext, LoadedAsset},
+    math::Vec2,
+};
+use roxmltree::{Document, Node};
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::tilemap_loader::{MapObject, MapObjectKind, TileData, TileLayer, TileMap};
+
+/// Tiled keeps the flips and rotations of a tile on the highest bits of its id
+const FLIP_FLAGS: u32 = 0xF000_0000;
+
+/// Custom properties of Tiled, every value written as text
+type Properties = HashMap<String, String>;
+
+/// The local id of a tile is its index on the `SpriteSheet`
+#[derive(Debug, Default)]
+struct TiledTileset {
+    first_gid: u32,
+    /// Path of an external tileset, relative to the map
+    source: Option<String>,
+    tile_count: u32,
+    /// Properties of each tile by its local id
+    tiles: HashMap<u32, Properties>,
+}
+
+#[derive(Debug)]
+struct TiledObject {
+    id: u32,
+    name: String,
+    /// `class` since Tiled 1.9, `type` before
+    class: String,
+    x: f32,
+    y: f32,
+    width: f32,
+    height: f32,
+    /// Tile objects are placed by their bottom left corner, the rest by the top left one
+    is_tile: bool,
+    properties: Properties,
+}
+
+#[derive(Debug)]
+enum TiledLayer {
+    Tiles {
+        data: Vec<u32>,
+        properties: Properties,
+    },
+    Objects(Vec<TiledObject>),
+}
+
+/// The parts of a Tiled map used by the game, the same for both file formats
+#[derive(Debug)]
+struct TiledMap {
+    width: usize,
+    height: usize,
+    tile_width: f32,
+    tile_height: f32,
+    tilesets: Vec<TiledTileset>,
+    /// Layers from the bottom to the top, without groups
+    layers: Vec<TiledLayer>,
+}
+
+/// Loads the maps made with Tiled, as XML (`.tmx`) or as JSON (`.tmj`).
+///
+/// Tile properties, or tile layer properties for every tile of the layer:
+/// `collider`, `encounter_region`, `trigger` and `tags` (separated by commas).
+/// Objects by class: `spawn`, `warp` with a `destination` object and `trigger` with an `encounter`
+#[derive(Default)]
+pub struct TiledMapLoader;
+
+impl AssetLoader for TiledMapLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), anyhow::Error>> {
+        Box::pin(async move {
+            let text = std::str::from_utf8(bytes)?;
+            let mut map = match extension(load_context.path()) {
+                Some("tmj") => parse_tmj(text)?,
+                _ => parse_tmx(text)?,
+            };
+            let map_dir = load_context
+                .path()
+                .parent()
+                .unwrap_or_else(|| Path::new(""));
+            for tileset in map.tilesets.iter_mut() {
+                let source = match tileset.source.take() {
+                    Some(source) => map_dir.join(source),
+                    None => continue,
+                };
+                let bytes = load_context.read_asset_bytes(&source).await?;
+                let text = std::str::from_utf8(&bytes)?;
+                *tileset = external_tileset(&source, text, tileset.first_gid)?;
+            }
+            load_context.set_default_asset(LoadedAsset::new(build_tile_map(map)?));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["tmx", "tmj"]
+    }
+}
+
+fn extension(path: &Path) -> Option<&str> {
+    path.extension().and_then(|extension| extension.to_str())
+}
+
+/// Tileset of its own file, as XML (`.tsx`) or as JSON (`.tsj`)
+fn external_tileset(
+    source: &Path,
+    text: &str,
+    first_gid: u32,
+) -> Result<TiledTileset, anyhow::Error> {
+    let external = match extension(source) {
+        Some("tsj") | Some("json") => parse_tsj(text),
+        _ => parse_tsx(text),
+    }
+    .map_err(|err| anyhow!("Invalid tileset '{}': {err}", source.display()))?;
+    Ok(TiledTileset {
+        first_gid,
+        ..external
+    })
+}
+
+fn build_tile_map(map: TiledMap) -> Result<TileMap, anyhow::Error> {
+    let mut tile_map = TileMap::default();
+    let mut objects = Vec::new();
+    for layer in map.layers.iter() {
+        match layer {
+            TiledLayer::Tiles { data, properties } => {
+                if data.len() != map.width * map.height {
+                    bail!(
+                        "Tile layer with {} tiles, the map is {}x{}",
+                        data.len(),
+                        map.width,
+                        map.height
+                    );
+                }
+                let mut rows = Vec::new();
+                for row in data.chunks(map.width) {
+                    let row = row
+                        .iter()
+                        .map(|gid| tile_data(&map.tilesets, *gid, properties))
+                        .collect::<Result<_, _>>()?;
+                    rows.push(row);
+                }
+                tile_map.layers.push(TileLayer { rows });
+            }
+            TiledLayer::Objects(layer_objects) => objects.extend(layer_objects),
+        }
+    }
+
+    for object in objects.iter() {
+        let kind = match object.class.as_str() {
+            "spawn" => MapObjectKind::Spawn,
+            "warp" => {
+                let destination = property(object, "destination")?;
+                let target = objects
+                    .iter()
+                    .find(|target| {
+                        target.name == *destination || target.id.to_string() == *destination
+                    })
+                    .ok_or_else(|| {
+                        anyhow!(
+                            "Warp '{}' goes to unknown object '{destination}'",
+                            object.name
+                        )
+                    })?;
+                MapObjectKind::Warp {
+                    destination: object_position(&map, target),
+                }
+            }
+            "trigger" => MapObjectKind::Trigger {
+                encounter: property(object, "encounter")?.clone(),
+            },
+            _ => continue,
+        };
+        tile_map.objects.push(MapObject {
+            name: object.name.clone(),
+            position: object_position(&map, object),
+            kind,
+        });
+    }
+    Ok(tile_map)
+}
+
+/// The properties of the tile go over the properties of its layer
+fn tile_data(
+    tilesets: &[TiledTileset],
+    gid: u32,
+    layer_properties: &Properties,
+) -> Result<Option<TileData>, anyhow::Error> {
+    let gid = gid & !FLIP_FLAGS;
+    if gid == 0 {
+        return Ok(None);
+    }
+    let tileset = tilesets
+        .iter()
+        .filter(|tileset| tileset.first_gid <= gid)
+        .max_by_key(|tileset| tileset.first_gid)
+        .filter(|tileset| gid - tileset.first_gid < tileset.tile_count)
+        .ok_or_else(|| anyhow!("Tile {gid} is not on any tileset"))?;
+    let id = gid - tileset.first_gid;
+    let empty = Properties::new();
+    let properties = tileset.tiles.get(&id).unwrap_or(&empty);
+    let get = |name: &str| properties.get(name).or_else(|| layer_properties.get(name));
+
+    Ok(Some(TileData {
+        sprite: id as usize,
+        collider: matches!(get("collider").map(String::as_str), Some("true")),
+        encounter_region: get("encounter_region").cloned(),
+        trigger: get("trigger").cloned(),
+        tags: [layer_properties.get("tags"), properties.get("tags")]
+            .into_iter()
+            .flatten()
+            .flat_map(|tags| tags.split(','))
+            .map(|tag| tag.trim().to_string())
+            .filter(|tag| !tag.is_empty())
+            .collect(),
+    }))
+}
+
+fn property<'a>(object: &'a TiledObject, name: &str) -> Result<&'a String, anyhow::Error> {
+    object
+        .properties
+        .get(name)
+        .ok_or_else(|| anyhow!("Object '{}' without the property '{name}'", object.name))
+}
+
+/// Center of the object, in tiles
+fn object_position(map: &TiledMap, object: &TiledObject) -> Vec2 {
+    let top = if object.is_tile {
+        object.y - object.height
+    } else {
+        object.y
+    };
+    Vec2::new(
+        (object.x + object.width / 2.0) / map.tile_width - 0.5,
+        (top + object.height / 2.0) / map.tile_height - 0.5,
+    )
+}
+
+// JSON format
+#[derive(Deserialize)]
+struct JsonMap {
+    width: usize,
+    height: usize,
+    tilewidth: f32,
+    tileheight: f32,
+    #[serde(default)]
+    infinite: bool,
+    tilesets: Vec<JsonTileset>,
+    layers: Vec<JsonLayer>,
+}
+
+/// External tilesets have the same fields, without the `firstgid`
+#[derive(Deserialize)]
+struct JsonTileset {
+    #[serde(default)]
+    firstgid: u32,
+    source: Option<String>,
+    #[serde(default)]
+    tilecount: u32,
+    #[serde(default)]
+    tiles: Vec<JsonTile>,
+}
+
+#[derive(Deserialize)]
+struct JsonTile {
+    id: u32,
+    #[serde(default)]
+    properties: Vec<JsonProperty>,
+}
+
+#[derive(Deserialize)]
+struct JsonProperty {
+    name: String,
+    value: Value,
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+enum JsonLayer {
+    Tilelayer {
+        #[serde(default)]
+        data: Value,
+        #[serde(default)]
+        properties: Vec<JsonProperty>,
+    },
+    Objectgroup {
+        objects: Vec<JsonObject>,
+    },
+    Group {
+        layers: Vec<JsonLayer>,
+    },
+    /// Image layers, the game doesn't use them
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Deserialize)]
+struct JsonObject {
+    id: u32,
+    #[serde(default)]
+    name: String,
+    #[serde(default, rename = "type")]
+    kind: String,
+    #[serde(default)]
+    class: String,
+    x: f32,
+    y: f32,
+    #[serde(default)]
+    width: f32,
+    #[serde(default)]
+    height: f32,
+    gid: Option<u32>,
+    #[serde(default)]
+    properties: Vec<JsonProperty>,
+}
+
+fn parse_tmj(text: &str) -> Result<TiledMap, anyhow::Error> {
+    let map: JsonMap = serde_json::from_str(text)?;
+    if map.infinite {
+        bail!("Infinite maps are not supported");
+    }
+    let mut layers = Vec::new();
+    json_layers(map.layers, &mut layers)?;
+    Ok(TiledMap {
+        width: map.width,
+        height: map.height,
+        tile_width: map.tilewidth,
+        tile_height: map.tileheight,
+        tilesets: map.tilesets.into_iter().map(json_tileset).collect(),
+        layers,
+    })
+}
+
+fn parse_tsj(text: &str) -> Result<TiledTileset, anyhow::Error> {
+    Ok(json_tileset(serde_json::from_str(text)?))
+}
+
+/// Flatten the groups, keeping the order of the layers
+fn json_layers(
+    layers: Vec<JsonLayer>,
+    tiled_layers: &mut Vec<TiledLayer>,
+) -> Result<(), anyhow::Error> {
+    for layer in layers {
+        match layer {
+            JsonLayer::Tilelayer { data, properties } => {
+                let data = match data {
+                    Value::Array(data) => data
+                        .iter()
+                        .map(|gid| match gid.as_u64() {
+                            Some(gid) => Ok(gid as u32),
+                            None => Err(anyhow!("Invalid tile {gid}")),
+                        })
+                        .collect::<Result<_, _>>()?,
+                    _ => bail!("Unsupported tile layer format, save the map with the CSV format"),
+                };
+                tiled_layers.push(TiledLayer::Tiles {
+                    data,
+                    properties: json_properties(properties),
+                });
+            }
+            JsonLayer::Objectgroup { objects } => {
+                let objects = objects
+                    .into_iter()
+                    .map(|object| TiledObject {
+                        id: object.id,
+                        name: object.name,
+                        class: if object.class.is_empty() {
+                            object.kind
+                        } else {
+                            object.class
+                        },
+                        x: object.x,
+                        y: object.y,
+                        width: object.width,
+                        height: object.height,
+                        is_tile: object.gid.is_some(),
+                        properties: json_properties(object.properties),
+                    })
+                    .collect();
+                tiled_layers.push(TiledLayer::Objects(objects));
+            }
+            JsonLayer::Group { layers } => json_layers(layers, tiled_layers)?,
+            JsonLayer::Other => {}
+        }
+    }
+    Ok(())
+}
+
+fn json_tileset(tileset: JsonTileset) -> TiledTileset {
+    TiledTileset {
+        first_gid: tileset.firstgid,
+        source: tileset.source,
+        tile_count: tileset.tilecount,
+        tiles: tileset
+            .tiles
+            .into_iter()
+            .map(|tile| (tile.id, json_properties(tile.properties)))
+            .collect(),
+    }
+}
+
+fn json_properties(properties: Vec<JsonProperty>) -> Properties {
+    properties
+        .into_iter()
+        .map(|property| {
+            let value = match property.value {
+                Value::String(value) => value,
+                value => value.to_string(),
+            };
+            (property.name, value)
+        })
+        .collect()
+}
+
+// XML format
+fn parse_tmx(text: &str) -> Result<TiledMap, anyhow::Error> {
+    let document = Document::parse(text)?;
+    let root = document.root_element();
+    if optional_attribute(root, "infinite", 0)? != 0 {
+        bail!("Infinite maps are not supported");
+    }
+    let tilesets = root
+        .children()
+        .filter(|node| node.has_tag_name("tileset"))
+        .map(xml_tileset)
+        .collect::<Result<_, _>>()?;
+    let mut layers = Vec::new();
+    xml_layers(root, &mut layers)?;
+    Ok(TiledMap {
+        width: attribute(root, "width")?,
+        height: attribute(root, "height")?,
+        tile_width: attribute(root, "tilewidth")?,
+        tile_height: attribute(root, "tileheight")?,
+        tilesets,
+        layers,
+    })
+}
+
+fn parse_tsx(text: &str) -> Result<TiledTileset, anyhow::Error> {
+    let document = Document::parse(text)?;
+    xml_tileset(document.root_element())
+}
+
+/// Flatten the groups, keeping the order of the layers
+fn xml_layers(parent: Node, tiled_layers: &mut Vec<TiledLayer>) -> Result<(), anyhow::Error> {
+    for node in parent.children().filter(Node::is_element) {
+        match node.tag_name().name() {
+            "layer" => {
+                let data = node
+                    .children()
+                    .find(|child| child.has_tag_name("data"))
+                    .ok_or_else(|| error_at(node, "Tile layer without <data>"))?;
+                let data = match data.attribute("encoding") {
+                    Some("csv") => data
+                        .text()
+                        .unwrap_or_default()
+                        .split(',')
+                        .map(|gid| {
+                            gid.trim()
+                                .parse()
+                                .map_err(|err| error_at(data, &format!("Invalid tile: {err}")))
+                        })
+                        .collect::<Result<_, _>>()?,
+                    None => data
+                        .children()
+                        .filter(|child| child.has_tag_name("tile"))
+                        .map(|tile| optional_attribute(tile, "gid", 0))
+                        .collect::<Result<_, _>>()?,
+                    Some(_) => {
+                        return Err(error_at(
+                            data,
+                            "Unsupported tile layer format, save the map with the CSV format",
+                        ))
+                    }
+                };
+                tiled_layers.push(TiledLayer::Tiles {
+                    data,
+                    properties: xml_properties(node),
+                });
+            }
+            "objectgroup" => {
+                let mut objects = Vec::new();
+                for object in node.children().filter(|child| child.has_tag_name("object")) {
+                    objects.push(TiledObject {
+                        id: attribute(object, "id")?,
+                        name: object.attribute("name").unwrap_or_default().to_string(),
+                        class: object
+                            .attribute("class")
+                            .or_else(|| object.attribute("type"))
+                            .unwrap_or_default()
+                            .to_string(),
+                        x: attribute(object, "x")?,
+                        y: attribute(object, "y")?,
+                        width: optional_attribute(object, "width", 0.0)?,
+                        height: optional_attribute(object, "height", 0.0)?,
+                        is_tile: object.has_attribute("gid"),
+                        properties: xml_properties(object),
+                    });
+                }
+                tiled_layers.push(TiledLayer::Objects(objects));
+            }
+            "group" => xml_layers(node, tiled_layers)?,
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// External tilesets are only the `firstgid` and the `source` on the map
+fn xml_tileset(tileset: Node) -> Result<TiledTileset, anyhow::Error> {
+    Ok(TiledTileset {
+        first_gid: optional_attribute(tileset, "firstgid", 0)?,
+        source: tileset.attribute("source").map(String::from),
+        tile_count: optional_attribute(tileset, "tilecount", 0)?,
+        tiles: tileset
+            .children()
+            .filter(|child| child.has_tag_name("tile"))
+            .map(|tile| Ok((attribute(tile, "id")?, xml_properties(tile))))
+            .collect::<Result<_, anyhow::Error>>()?,
+    })
+}
+
+/// Long texts are written inside the property instead of on `value`
+fn xml_properties(node: Node) -> Properties {
+    node.children()
+        .filter(|child| child.has_tag_name("properties"))
+        .flat_map(|properties| properties.children())
+        .filter(|property| property.has_tag_name("property"))
+        .filter_map(|property| {
+            let value = property.attribute("value").or_else(|| property.text())?;
+            Some((property.attribute("name")?.to_string(), value.to_string()))
+        })
+        .collect()
+}
+
+fn attribute<T>(node: Node, name: &str) -> Result<T, anyhow::Error>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    match node.attribute(name) {
+        Some(value) => value
+            .parse()
+            .map_err(|err| error_at(node, &format!("Invalid attribute '{name}': {err}"))),
+        None => Err(error_at(node, &format!("Missing attribute '{name}'"))),
+    }
+}
+
+fn optional_attribute<T>(node: Node, name: &str, default: T) -> Result<T, anyhow::Error>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    if node.has_attribute(name) {
+        attribute(node, name)
+    } else {
+        Ok(default)
+    }
+}
+
+fn error_at(node: Node, message: &str) -> anyhow::Error {
+    let position = node.document().text_pos_at(node.range().start);
+    anyhow!(
+        "{message} on <{}> at line {}, column {}",
+        node.tag_name().name(),
+        position.row,
+        position.col
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Build the map like the loader, with `tiles.tsx` as the only external tileset
+    fn load(mut map: TiledMap) -> TileMap {
+        for tileset in map.tilesets.iter_mut() {
+            if let Some(source) = tileset.source.take() {
+                assert_eq!(source, "tiles.tsx");
+                let text = include_str!("../assets/maps/tiles.tsx");
+                *tileset = external_tileset(Path::new(&source), text, tileset.first_gid).unwrap();
+            }
+        }
+        build_tile_map(map).unwrap()
+    }
+
+    fn tile(tile_map: &TileMap, layer: usize, x: usize, y: usize) -> &TileData {
+        tile_map.layers[layer].rows[y][x].as_ref().unwrap()
+    }
+
+    #[test]
+    fn tmx_and_tmj_build_the_same_map() {
+        let tmx = load(parse_tmx(include_str!("../assets/maps/test.tmx")).unwrap());
+        let tmj = load(parse_tmj(include_str!("../assets/maps/test.tmj")).unwrap());
+        assert_eq!(tmx, tmj);
+    }
+
+    #[test]
+    fn tile_layers_use_the_properties_of_the_tileset_and_the_layer() {
+        let tile_map = load(parse_tmx(include_str!("../assets/maps/test.tmx")).unwrap());
+        assert_eq!(tile_map.layers.len(), 2);
+        assert!(tile_map.layers.iter().all(|layer| layer.rows.len() == 6));
+
+        // The Walls layer makes every tile a collider, empty tiles stay empty
+        let wall = tile(&tile_map, 1, 0, 0);
+        assert_eq!(wall.sprite, 3);
+        assert!(wall.collider);
+        assert!(tile_map.layers[1].rows[1][1].is_none());
+        assert!(!tile(&tile_map, 0, 0, 0).collider);
+
+        let forest = tile(&tile_map, 0, 2, 2);
+        assert_eq!(forest.sprite, 1);
+        assert_eq!(forest.encounter_region.as_deref(), Some("Forest"));
+        assert!(forest.tags.is_empty());
+        let fields = tile(&tile_map, 0, 5, 3);
+        assert_eq!(fields.sprite, 7);
+        assert_eq!(fields.encounter_region.as_deref(), Some("Fields"));
+        assert_eq!(fields.tags, ["grass"]);
+    }
+
+    #[test]
+    fn objects_are_placed_in_tiles() {
+        let tile_map = load(parse_tmx(include_str!("../assets/maps/test.tmx")).unwrap());
+        assert_eq!(tile_map.spawn(), Some(Vec2::new(1.0, 1.0)));
+        // The destination of the warp is not an object of the game by itself
+        assert_eq!(
+            tile_map.objects,
+            [
+                MapObject {
+                    name: String::from("Start"),
+                    position: Vec2::new(1.0, 1.0),
+                    kind: MapObjectKind::Spawn,
+                },
+                MapObject {
+                    name: String::from("Cave entrance"),
+                    position: Vec2::new(6.0, 1.0),
+                    kind: MapObjectKind::Warp {
+                        destination: Vec2::new(1.0, 4.0),
+                    },
+                },
+                MapObject {
+                    name: String::from("Cave Guardian"),
+                    position: Vec2::new(6.0, 4.0),
+                    kind: MapObjectKind::Trigger {
+                        encounter: String::from("Cave Guardian"),
+                    },
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,188 @@
+use std::collections::HashMap;
+
+use anyhow::anyhow;
+use bevy::{
+    asset::{AssetLoader, BoxedFuture, LoadContext, LoadedAsset},
+    math::Vec2,
+    reflect::TypeUuid,
+};
+use serde::Deserialize;
+
+/// Line between the legend and the tiles of the map
+const LEGEND_SEPARATOR: &str = "---";
+
+/// What a character of the map means
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct TileData {
+    /// Index on the `SpriteSheet`
+    pub sprite: usize,
+    #[serde(default)]
+    pub collider: bool,
+    /// Region of the `EncounterDatabase` for random encounters
+    #[serde(default)]
+    pub encounter_region: Option<String>,
+    /// Fixed encounter of the `EncounterDatabase` started by stepping on the tile
+    #[serde(default)]
+    pub trigger: Option<String>,
+    /// Extra tags for the tile, free for the game to use
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+/// Header of the map file, written in RON
+#[derive(Debug, Deserialize)]
+struct TileLegend {
+    tiles: HashMap<char, TileData>,
+}
+
+/// Grid of tiles, `None` where the layer is empty
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TileLayer {
+    /// Rows of tiles from top to bottom
+    pub rows: Vec<Vec<Option<TileData>>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum MapObjectKind {
+    /// Start position of the player
+    Spawn,
+    /// Moves the player to the destination, in tiles
+    Warp { destination: Vec2 },
+    /// Starts a fixed encounter of the `EncounterDatabase`
+    Trigger { encounter: String },
+}
+
+/// Thing placed on the map outside of the tile grid
+#[derive(Debug, Clone, PartialEq)]
+pub struct MapObject {
+    pub name: String,
+    /// Position in tiles, the center of the first tile is `(0, 0)`
+    pub position: Vec2,
+    pub kind: MapObjectKind,
+}
+
+/// Map shown on the overworld, loaded from text (ex: `map.txt`) or from Tiled (ex: `map.tmx`)
+#[derive(Debug, Default, PartialEq, TypeUuid)]
+#[uuid = "b7e3c1a9-5d2f-4a86-9c4e-3f1d8a6b2e50"]
+pub struct TileMap {
+    /// Layers from the bottom to the top
+    pub layers: Vec<TileLayer>,
+    pub objects: Vec<MapObject>,
+}
+
+impl TileMap {
+    /// Start position of the player, in tiles
+    pub fn spawn(&self) -> Option<Vec2> {
+        self.objects.iter().find_map(|object| match object.kind {
+            MapObjectKind::Spawn => Some(object.position),
+            _ => None,
+        })
+    }
+}
+
+/// Loads the text maps, a legend on top and the tiles below, one character each
+#[derive(Default)]
+pub struct TileMapLoader;
+
+impl AssetLoader for TileMapLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), anyhow::Error>> {
+        Box::pin(async move {
+            let tile_map = parse_tile_map(std::str::from_utf8(bytes)?)?;
+            load_context.set_default_asset(LoadedAsset::new(tile_map));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["txt"]
+    }
+}
+
+/// Every character of the map must be on the legend, the errors use the lines of the file
+fn parse_tile_map(text: &str) -> Result<TileMap, anyhow::Error> {
+    let lines: Vec<_> = text.lines().collect();
+    let separator = lines
+        .iter()
+        .position(|line| line.trim() == LEGEND_SEPARATOR)
+        .ok_or_else(|| anyhow!("Missing the '{LEGEND_SEPARATOR}' line after the legend"))?;
+    let legend: TileLegend = ron::from_str(&lines[..separator].join("\n"))
+        .map_err(|err| anyhow!("Invalid legend: {err}"))?;
+
+    let mut rows = Vec::new();
+    for (y, line) in lines[separator + 1..].iter().enumerate() {
+        let mut row = Vec::new();
+        for (x, ch) in line.chars().enumerate() {
+            let tile = legend.tiles.get(&ch).ok_or_else(|| {
+                anyhow!(
+                    "Unknown tile '{ch}' at line {}, column {}",
+                    separator + y + 2,
+                    x + 1
+                )
+            })?;
+            row.push(Some(tile.clone()));
+        }
+        rows.push(row);
+    }
+    Ok(TileMap {
+        layers: vec![TileLayer { rows }],
+        objects: Vec::new(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LEGEND: &str = "(
+    tiles: {
+        'o': (sprite: 3, collider: true),
+        '~': (sprite: 7, encounter_region: Some(\"Fields\"), tags: [\"grass\"]),
+    },
+)";
+
+    fn map(tiles: &str) -> String {
+        format!("{LEGEND}\n{LEGEND_SEPARATOR}\n{tiles}")
+    }
+
+    #[test]
+    fn tiles_use_the_legend() {
+        let tile_map = parse_tile_map(&map("ooo\no~o")).unwrap();
+        assert_eq!(tile_map.layers.len(), 1);
+        let rows = &tile_map.layers[0].rows;
+        assert_eq!(rows.len(), 2);
+        assert!(rows.iter().all(|row| row.len() == 3));
+
+        let wall = rows[0][0].as_ref().unwrap();
+        assert_eq!(wall.sprite, 3);
+        assert!(wall.collider);
+        let grass = rows[1][1].as_ref().unwrap();
+        assert_eq!(grass.sprite, 7);
+        assert!(!grass.collider);
+        assert_eq!(grass.encounter_region.as_deref(), Some("Fields"));
+        assert_eq!(grass.tags, ["grass"]);
+    }
+
+    #[test]
+    fn unknown_tiles_report_their_line_on_the_file() {
+        // 6 lines of legend and the separator go before the tiles
+        let err = parse_tile_map(&map("ooo\no~X")).unwrap_err();
+        assert_eq!(err.to_string(), "Unknown tile 'X' at line 9, column 3");
+    }
+
+    #[test]
+    fn the_separator_is_required() {
+        let err = parse_tile_map(&format!("{LEGEND}\nooo")).unwrap_err();
+        assert_eq!(err.to_string(), "Missing the '---' line after the legend");
+    }
+
+    #[test]
+    fn invalid_legends_fail() {
+        // The sprite is required
+        let err = parse_tile_map("(tiles: {'o': (collider: true)})\n---\no").unwrap_err();
+        assert!(err.to_string().starts_with("Invalid legend:"), "{err}");
+    }
+}
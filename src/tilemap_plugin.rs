@@ -1,7 +1,12 @@
-use bevy::prelude::*;
+// The bevy 0.7 `Bundle` derive forgets the fields with `std::mem::forget`
+#![allow(clippy::forget_non_drop)]
+
+use bevy::{asset::LoadState, prelude::*};
 
 use crate::{
-    common_component::{Collider, EncounterSpawn},
+    common_component::{Collider, EncounterSpawn, EncounterTrigger, TileTags, Warp},
+    tiled_loader::TiledMapLoader,
+    tilemap_loader::{MapObjectKind, TileMap, TileMapLoader},
     AppState, SpriteSheet, TILE_SIZE,
 };
 
@@ -17,65 +22,200 @@ struct MapBundle {
     g_transform: GlobalTransform,
 }
 
+/// Map shown on the overworld
+pub struct TileMapHandle(pub Handle<TileMap>);
+
+/// Path of the overworld map inside the assets folder, a text map or a Tiled map (`.tmx`, `.tmj`)
+pub struct MapPath(pub String);
+
+impl Default for MapPath {
+    fn default() -> Self {
+        Self(String::from("map.txt"))
+    }
+}
+
+/// The map loaded or changed on disk after it was built, the overworld builds it again
+#[derive(Default)]
+struct MapChanged(bool);
+
 pub struct TilemapPlugin;
 impl Plugin for TilemapPlugin {
     fn build(&self, app: &mut App) {
+        app.init_resource::<MapPath>()
+            .init_resource::<MapChanged>()
+            .add_asset::<TileMap>()
+            .init_asset_loader::<TileMapLoader>()
+            .init_asset_loader::<TiledMapLoader>()
+            .add_startup_system(load_map)
+            // The changes that happen outside of the overworld wait for it
+            .add_system(watch_map);
+
         app.add_system_set(SystemSet::on_enter(AppState::OverWorld).with_system(creat_simple_map))
+            .add_system_set(SystemSet::on_update(AppState::OverWorld).with_system(reload_map))
             // On combat hide the map
             .add_system_set(SystemSet::on_enter(AppState::Combat).with_system(hide_map))
             // Always that the Overworld start show the map
-            .add_system_set(SystemSet::on_resume(AppState::OverWorld).with_system(show_map));
+            .add_system_set(SystemSet::on_resume(AppState::OverWorld).with_system(show_map))
+            .add_system_set(SystemSet::on_exit(AppState::OverWorld).with_system(despawn_map));
     }
 }
 
-fn creat_simple_map(mut commands: Commands, sprite_sheet: Res<SpriteSheet>) {
-    // TODO:  Change to a custom load
-    let map = include_str!("../assets/map.txt");
-
-    let mut spawn_tile = |i: usize, x: usize, y: usize, collider: bool, spawn: bool| {
-        let tile = commands
-            .spawn_bundle(SpriteSheetBundle {
-                sprite: TextureAtlasSprite::new(i),
-                texture_atlas: sprite_sheet.0.clone(),
-                transform: Transform::from_xyz(
-                    (x as f32 - 1.0) * TILE_SIZE,
-                    (1.0 - (y as f32)) * TILE_SIZE,
-                    9.0,
-                ),
-                ..Default::default()
-            })
-            .id();
-        if collider {
-            commands.entity(tile).insert(Collider);
+fn load_map(mut commands: Commands, assets: Res<AssetServer>, map_path: Res<MapPath>) {
+    commands.insert_resource(TileMapHandle(assets.load(map_path.0.as_str())));
+}
+
+/// Without the map loaded the overworld starts empty, `reload_map` builds it once it loads
+fn creat_simple_map(
+    mut commands: Commands,
+    sprite_sheet: Res<SpriteSheet>,
+    assets: Res<AssetServer>,
+    map_handle: Res<TileMapHandle>,
+    maps: Res<Assets<TileMap>>,
+    mut map_changed: ResMut<MapChanged>,
+) {
+    let name = map_name(&assets, &map_handle);
+    match maps.get(&map_handle.0) {
+        Some(map) => {
+            map_changed.0 = false;
+            spawn_map(&mut commands, &sprite_sheet, &name, map);
         }
-        if spawn {
-            commands.entity(tile).insert(EncounterSpawn);
+        None if assets.get_load_state(&map_handle.0) == LoadState::Failed => {
+            error!("Failed to load the map {name}, the overworld has no map")
         }
-        tile
+        None => warn!("The map {name} is not loaded yet, it is built once it loads"),
+    }
+}
+
+fn watch_map(
+    mut map_event: EventReader<AssetEvent<TileMap>>,
+    map_handle: Res<TileMapHandle>,
+    mut map_changed: ResMut<MapChanged>,
+) {
+    if map_event.iter().any(|event| match event {
+        AssetEvent::Created { handle } | AssetEvent::Modified { handle } => *handle == map_handle.0,
+        _ => false,
+    }) {
+        map_changed.0 = true;
+    }
+}
+
+/// Build the map again when the file loads or changes on disk, only while the overworld is shown
+fn reload_map(
+    mut commands: Commands,
+    sprite_sheet: Res<SpriteSheet>,
+    assets: Res<AssetServer>,
+    map_handle: Res<TileMapHandle>,
+    maps: Res<Assets<TileMap>>,
+    mut map_changed: ResMut<MapChanged>,
+    map_query: Query<Entity, With<Map>>,
+) {
+    let map = match maps.get(&map_handle.0) {
+        Some(map) if map_changed.0 => map,
+        _ => return,
     };
+    map_changed.0 = false;
+    for ent in map_query.iter() {
+        commands.entity(ent).despawn_recursive();
+    }
+    spawn_map(
+        &mut commands,
+        &sprite_sheet,
+        &map_name(&assets, &map_handle),
+        map,
+    );
+}
+
+/// Position on the world of a position in tiles of the map
+pub fn tile_translation(position: Vec2, z: f32) -> Vec3 {
+    Vec3::new(
+        (position.x - 1.0) * TILE_SIZE,
+        (1.0 - position.y) * TILE_SIZE,
+        z,
+    )
+}
+
+fn map_name(assets: &AssetServer, map_handle: &TileMapHandle) -> String {
+    match assets.get_handle_path(&map_handle.0) {
+        Some(path) => path.path().display().to_string(),
+        None => String::from("Map"),
+    }
+}
 
-    let mut tiles = Vec::new();
-    for (y, line) in map.lines().enumerate() {
-        for (x, ch) in line.chars().enumerate() {
-            let tile = match ch {
-                'o' => spawn_tile(3, x, y, true, false),
-                '#' => spawn_tile(5, x, y, true, false),
-                'g' => spawn_tile(4, x, y, false, false),
-                ',' => spawn_tile(6, x, y, false, false),
-                '~' => spawn_tile(7, x, y, false, true),
-                _ => spawn_tile(0, x, y, false, false),
-            };
-            tiles.push(tile);
+/// Each layer goes a little over the previous one, the objects have no sprite
+fn spawn_map(commands: &mut Commands, sprite_sheet: &SpriteSheet, name: &str, map: &TileMap) {
+    let mut children = Vec::new();
+    for (z, layer) in map.layers.iter().enumerate() {
+        for (y, line) in layer.rows.iter().enumerate() {
+            for (x, tile_data) in line.iter().enumerate() {
+                let tile_data = match tile_data {
+                    Some(tile_data) => tile_data,
+                    None => continue,
+                };
+                let position = Vec2::new(x as f32, y as f32);
+                let mut tile = commands.spawn_bundle(SpriteSheetBundle {
+                    sprite: TextureAtlasSprite::new(tile_data.sprite),
+                    texture_atlas: sprite_sheet.0.clone(),
+                    transform: Transform::from_translation(tile_translation(
+                        position,
+                        9.0 + z as f32 * 0.01,
+                    )),
+                    ..Default::default()
+                });
+                if tile_data.collider {
+                    tile.insert(Collider);
+                }
+                if let Some(region) = &tile_data.encounter_region {
+                    tile.insert(EncounterSpawn {
+                        region: region.clone(),
+                    });
+                }
+                if let Some(encounter) = &tile_data.trigger {
+                    tile.insert(EncounterTrigger {
+                        encounter: encounter.clone(),
+                    });
+                }
+                if !tile_data.tags.is_empty() {
+                    tile.insert(TileTags(tile_data.tags.clone()));
+                }
+                children.push(tile.id());
+            }
+        }
+    }
+    for object in map.objects.iter() {
+        let mut ent = commands.spawn_bundle(TransformBundle::from_transform(
+            Transform::from_translation(tile_translation(object.position, 9.0)),
+        ));
+        ent.insert(Name::new(object.name.clone()));
+        match &object.kind {
+            // The player looks for the spawn on the map asset
+            MapObjectKind::Spawn => {}
+            MapObjectKind::Warp { destination } => {
+                ent.insert(Warp {
+                    destination: tile_translation(*destination, 0.0).truncate(),
+                });
+            }
+            MapObjectKind::Trigger { encounter } => {
+                ent.insert(EncounterTrigger {
+                    encounter: encounter.clone(),
+                });
+            }
         }
+        children.push(ent.id());
     }
     commands
         .spawn_bundle(MapBundle {
-            name: Name::new("map.txt"),
+            name: Name::new(name.to_string()),
             tag: Map,
             transform: Transform::default(),
             g_transform: GlobalTransform::default(),
         })
-        .push_children(&tiles);
+        .push_children(&children);
+}
+
+fn despawn_map(mut commands: Commands, map_query: Query<Entity, With<Map>>) {
+    for ent in map_query.iter() {
+        commands.entity(ent).despawn_recursive();
+    }
 }
 
 fn hide_map(
@@ -0,0 +1,172 @@
+use bevy::prelude::*;
+use rand::Rng;
+
+use crate::{
+    combat_plugin::CombatState,
+    common_component::CombatStats,
+    enemy_plugin::{Enemy, Reward},
+    experience_plugin::{ExperienceEvent, LevelUpEvent},
+    fadeout_plugin::FadeoutConfigResource,
+    inventory_plugin::Inventory,
+    party_plugin::PartyMember,
+    AppState, UiFont,
+};
+
+const TEXT_COLOR: Color = Color::rgb(1.0, 1.0, 1.0);
+
+// Plugin struct definitions
+#[derive(Debug, Component)]
+struct VictoryRoot;
+
+#[derive(Debug, Component)]
+struct VictoryText;
+
+pub struct VictoryPlugin;
+impl Plugin for VictoryPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(SystemSet::on_enter(CombatState::Victory).with_system(collect_rewards))
+            .add_system_set(
+                SystemSet::on_update(CombatState::Victory)
+                    .with_system(show_level_ups)
+                    .with_system(confirm_victory),
+            )
+            .add_system_set(SystemSet::on_exit(CombatState::Victory).with_system(despawn_victory));
+    }
+}
+
+fn text_section(value: String, font: &Handle<Font>) -> TextSection {
+    TextSection {
+        value,
+        style: TextStyle {
+            font: font.clone(),
+            font_size: 20.0,
+            color: TEXT_COLOR,
+        },
+    }
+}
+
+/// Add up the rewards of every enemy, roll their loot and show the results.
+/// Every living party member gets the whole experience
+fn collect_rewards(
+    mut commands: Commands,
+    font: Res<UiFont>,
+    mut inventory: ResMut<Inventory>,
+    mut experience_event: EventWriter<ExperienceEvent>,
+    enemy_query: Query<&Reward, With<Enemy>>,
+    member_query: Query<(Entity, &CombatStats), With<PartyMember>>,
+) {
+    let mut rng = rand::thread_rng();
+    let xp = enemy_query.iter().map(|reward| reward.xp).sum();
+    let gold: u32 = enemy_query.iter().map(|reward| reward.gold).sum();
+    let drops: Vec<_> = enemy_query
+        .iter()
+        .flat_map(|reward| reward.loot.iter())
+        .filter(|drop| rng.gen::<f32>() < drop.chance)
+        .map(|drop| drop.item.clone())
+        .collect();
+
+    // The fallen members don't get experience
+    for (target, _) in member_query
+        .iter()
+        .filter(|(_, combat_stats)| combat_stats.hp > 0)
+    {
+        experience_event.send(ExperienceEvent { target, xp });
+    }
+    inventory.gold += gold;
+    for item in drops.iter() {
+        inventory.add_item(item, 1);
+    }
+
+    let mut lines = vec![
+        String::from("Victory!\n"),
+        format!("Gained {xp} XP\n"),
+        format!("Found {gold} gold\n"),
+    ];
+    lines.extend(drops.iter().map(|item| format!("Found {item}\n")));
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..Default::default()
+            },
+            color: UiColor(Color::NONE),
+            ..Default::default()
+        })
+        .insert(Name::new("Victory"))
+        .insert(VictoryRoot)
+        .with_children(|parent| {
+            parent
+                .spawn_bundle(NodeBundle {
+                    style: Style {
+                        padding: Rect::all(Val::Px(16.0)),
+                        ..Default::default()
+                    },
+                    color: UiColor(Color::rgba(0.0, 0.0, 0.3, 0.8)),
+                    ..Default::default()
+                })
+                .with_children(|parent| {
+                    parent
+                        .spawn_bundle(TextBundle {
+                            text: Text {
+                                sections: lines
+                                    .into_iter()
+                                    .map(|line| text_section(line, &font.0))
+                                    .collect(),
+                                alignment: TextAlignment::default(),
+                            },
+                            ..Default::default()
+                        })
+                        .insert(VictoryText);
+                });
+        });
+}
+
+/// The xp is applied by the `ExperiencePlugin`, the level ups arrive after the screen is shown
+fn show_level_ups(
+    font: Res<UiFont>,
+    mut level_up_event: EventReader<LevelUpEvent>,
+    name_query: Query<&Name>,
+    mut text_query: Query<&mut Text, With<VictoryText>>,
+) {
+    for event in level_up_event.iter() {
+        let name = match name_query.get(event.target) {
+            Ok(name) => name,
+            Err(_) => continue,
+        };
+        for mut text in text_query.iter_mut() {
+            text.sections.push(text_section(
+                format!("{name} reached level {}\n", event.level),
+                &font.0,
+            ));
+        }
+    }
+}
+
+fn confirm_victory(
+    mut commands: Commands,
+    mut keyboard: ResMut<Input<KeyCode>>,
+    mut state: ResMut<State<AppState>>,
+) {
+    if !keyboard.just_pressed(KeyCode::Space) && !keyboard.just_pressed(KeyCode::Return) {
+        return;
+    }
+    keyboard.reset(KeyCode::Space);
+    keyboard.reset(KeyCode::Return);
+    // Without a next state the fadeout pops back to the `OverWorld`
+    commands.insert_resource(FadeoutConfigResource {
+        fadeout_duration: 0.75,
+        next_state: None,
+        position: Vec3::ZERO,
+    });
+    state
+        .set(AppState::Fadeout)
+        .expect("Error setting state to App::Fadeout 'Victory plugin'");
+}
+
+fn despawn_victory(mut commands: Commands, victory_query: Query<Entity, With<VictoryRoot>>) {
+    for ent in victory_query.iter() {
+        commands.entity(ent).despawn_recursive();
+    }
+}